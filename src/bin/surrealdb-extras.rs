@@ -0,0 +1,4 @@
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    surrealdb_extras::cli::run().await
+}
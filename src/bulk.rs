@@ -0,0 +1,265 @@
+use std::{
+    future::{Future, IntoFuture},
+    marker::PhantomData,
+    pin::Pin,
+};
+
+use surrealdb::{
+    Connection, IndexedResults, Surreal,
+    types::{SurrealValue, Table, Value, Variables},
+};
+
+use crate::{Filter, RecordData, SurrealTableInfo};
+
+/// single `UPDATE`/`DELETE ... WHERE ... RETURN` on table `T`,
+/// created by [SurrealTableInfo::update_where] and [SurrealTableInfo::delete_where]
+///
+/// awaiting it returns the changed items, or `R` after [none](Self::none)/[count](Self::count)
+#[must_use = "queries do nothing unless awaited"]
+pub struct BulkQuery<'a, C: Connection, T: SurrealTableInfo, R = Vec<RecordData<T>>> {
+    conn: &'a Surreal<C>,
+    statement: String,
+    vars: Variables,
+    output: &'static str,
+    _ty: PhantomData<fn() -> (T, R)>,
+}
+
+impl<'a, C: Connection, T: SurrealTableInfo> BulkQuery<'a, C, T> {
+    fn new(
+        conn: &'a Surreal<C>,
+        statement: &str,
+        filter: Filter<T>,
+        mut vars: Variables,
+        output: &'static str,
+    ) -> Self {
+        let filter = filter.build(&mut vars);
+        vars.insert("table", Table::from(T::name()));
+
+        Self {
+            conn,
+            statement: format!("{statement} WHERE {filter}"),
+            vars,
+            output,
+            _ty: PhantomData,
+        }
+    }
+
+    /// `UPDATE ... MERGE $patch WHERE ... RETURN AFTER`
+    pub(crate) fn update(
+        conn: &'a Surreal<C>,
+        filter: Filter<T>,
+        patch: impl SurrealValue,
+    ) -> Self {
+        let mut vars = Variables::new();
+        vars.insert("patch", patch.into_value());
+
+        Self::new(
+            conn,
+            "UPDATE type::table($table) MERGE $patch",
+            filter,
+            vars,
+            "AFTER",
+        )
+    }
+
+    /// `DELETE ... WHERE ... RETURN BEFORE`
+    pub(crate) fn delete(conn: &'a Surreal<C>, filter: Filter<T>) -> Self {
+        Self::new(
+            conn,
+            "DELETE type::table($table)",
+            filter,
+            Variables::new(),
+            "BEFORE",
+        )
+    }
+}
+
+impl<'a, C: Connection, T: SurrealTableInfo, R> BulkQuery<'a, C, T, R> {
+    fn with<R2>(self, output: &'static str) -> BulkQuery<'a, C, T, R2> {
+        BulkQuery {
+            conn: self.conn,
+            statement: self.statement,
+            vars: self.vars,
+            output,
+            _ty: PhantomData,
+        }
+    }
+
+    /// `RETURN BEFORE`, the items as they were before the change
+    pub fn before(self) -> BulkQuery<'a, C, T> {
+        self.with("BEFORE")
+    }
+
+    /// `RETURN AFTER`, the items after the change (nothing for DELETE)
+    pub fn after(self) -> BulkQuery<'a, C, T> {
+        self.with("AFTER")
+    }
+
+    /// `RETURN NONE`
+    pub fn none(self) -> BulkQuery<'a, C, T, ()> {
+        self.with("NONE")
+    }
+
+    /// number of changed items
+    pub fn count(self) -> BulkQuery<'a, C, T, usize> {
+        self.with("VALUE true")
+    }
+}
+
+/// output of a [BulkQuery] for the `RETURN` clause
+#[doc(hidden)]
+pub trait BulkOutput: Sized {
+    fn take(res: IndexedResults) -> surrealdb::Result<Self>;
+}
+
+impl<T: SurrealTableInfo> BulkOutput for Vec<RecordData<T>> {
+    fn take(mut res: IndexedResults) -> surrealdb::Result<Self> {
+        // DELETE ... RETURN AFTER yields NONE per item
+        let items: Vec<Option<RecordData<T>>> = res.take(0)?;
+        Ok(items.into_iter().flatten().collect())
+    }
+}
+
+impl BulkOutput for () {
+    fn take(mut res: IndexedResults) -> surrealdb::Result<Self> {
+        res.take::<Value>(0).map(|_| ())
+    }
+}
+
+impl BulkOutput for usize {
+    fn take(mut res: IndexedResults) -> surrealdb::Result<Self> {
+        res.take::<Vec<Value>>(0).map(|values| values.len())
+    }
+}
+
+impl<'a, C, T, R> IntoFuture for BulkQuery<'a, C, T, R>
+where
+    C: Connection,
+    T: SurrealTableInfo,
+    R: BulkOutput + Send + 'a,
+{
+    type Output = surrealdb::Result<R>;
+    type IntoFuture = Pin<Box<dyn Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let Self {
+            conn,
+            statement,
+            vars,
+            output,
+            ..
+        } = self;
+
+        Box::pin(async move {
+            let res = conn
+                .query(format!("{statement} RETURN {output}"))
+                .bind(vars)
+                .await?;
+            R::take(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::{Surreal, engine::local::Db, types::SurrealValue};
+
+    use crate::{RecordData, SurrealTable, SurrealTableInfo, filter, test_conn};
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = task)]
+    struct Task {
+        title: String,
+        done: bool,
+        priority: i64,
+    }
+
+    #[derive(SurrealValue)]
+    struct Done {
+        done: bool,
+    }
+
+    async fn conn_with_tasks() -> Surreal<Db> {
+        let conn = test_conn().await;
+        conn.query(
+            "CREATE task:a SET title = 'a', done = false, priority = 1;
+            CREATE task:b SET title = 'b', done = false, priority = 2;
+            CREATE task:c SET title = 'c', done = false, priority = 3;",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        conn
+    }
+
+    fn titles(items: &[RecordData<Task>]) -> Vec<(&str, bool)> {
+        let mut titles = items
+            .iter()
+            .map(|item| (item.data.title.as_str(), item.data.done))
+            .collect::<Vec<_>>();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn update_returns() {
+        let conn = conn_with_tasks().await;
+
+        let after = Task::update_where(&conn, filter!(Task, priority >= 2), Done { done: true })
+            .await
+            .unwrap();
+        assert_eq!(titles(&after), [("b", true), ("c", true)]);
+
+        let before = Task::update_where(&conn, filter!(Task, title == "c"), Done { done: false })
+            .before()
+            .await
+            .unwrap();
+        assert_eq!(titles(&before), [("c", true)]);
+
+        let count = Task::update_where(&conn, filter!(Task, done == false), Done { done: true })
+            .count()
+            .await
+            .unwrap();
+        assert_eq!(count, 2);
+
+        Task::update_where(&conn, filter!(Task, priority == 1), Done { done: false })
+            .none()
+            .await
+            .unwrap();
+        assert_eq!(
+            Task::count(&conn, Some(filter!(Task, done == true)))
+                .await
+                .unwrap(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn delete_returns() {
+        let conn = conn_with_tasks().await;
+
+        let before = Task::delete_where(&conn, filter!(Task, title == "a"))
+            .await
+            .unwrap();
+        assert_eq!(titles(&before), [("a", false)]);
+
+        let after = Task::delete_where(&conn, filter!(Task, title == "b"))
+            .after()
+            .await
+            .unwrap();
+        assert!(after.is_empty());
+
+        let count = Task::delete_where(&conn, filter!(Task, priority > 5))
+            .count()
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        Task::delete_where(&conn, filter!(Task, priority == 3))
+            .none()
+            .await
+            .unwrap();
+        assert_eq!(Task::count(&conn, None).await.unwrap(), 0);
+    }
+}
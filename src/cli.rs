@@ -0,0 +1,371 @@
+//! `surrealdb-extras` command line for schema export and diff, migrations, codegen and seeding
+//!
+//! the `surrealdb-extras` binary only knows the tables of this crate,
+//! run the command line from a binary of your crate to use your derived tables:
+//! ```ignore
+//! // src/bin/db.rs
+//! #[tokio::main]
+//! async fn main() -> std::process::ExitCode {
+//!     surrealdb_extras::cli::run().await
+//! }
+//! ```
+//! ```sh
+//! cargo run --bin db -- --url mem:// --migrations migrations schema diff
+//! cargo run --bin db -- --url ws://localhost:8000 --ns app --db main migrate up
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand};
+use surrealdb::{
+    Surreal,
+    engine::any::{self, Any},
+    opt::auth::Root,
+};
+
+use crate::{
+    DefineStrategy, SurrealExt, codegen,
+    migration::Migrator,
+    query::first_error,
+    registry::registered_tables,
+    schema::{Change, export_schema, statements},
+};
+
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Parser)]
+#[command(name = "surrealdb-extras", version, about)]
+pub struct Cli {
+    /// database url, e.g. `ws://localhost:8000` or `mem://` for an in-memory database
+    #[arg(long, env = "SURREAL_URL", default_value = "mem://", global = true)]
+    pub url: String,
+    #[arg(long, env = "SURREAL_NS", default_value = "test", global = true)]
+    pub ns: String,
+    #[arg(long, env = "SURREAL_DB", default_value = "test", global = true)]
+    pub db: String,
+    /// root user, signs in if `pass` is set too
+    #[arg(long, env = "SURREAL_USER", global = true)]
+    pub user: Option<String>,
+    #[arg(long, env = "SURREAL_PASS", global = true)]
+    pub pass: Option<String>,
+    /// only tables registered in this module, e.g. `my_app::models`
+    #[arg(long, default_value = "", global = true)]
+    pub path: String,
+    /// applies the migrations of the directory before `schema diff`, `codegen` and `seed`,
+    /// e.g. to check them with `mem://`, `migrate` uses its own `--dir`
+    #[arg(long, global = true)]
+    pub migrations: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// registered tables
+    #[command(subcommand)]
+    Schema(SchemaCommand),
+    /// migrations of a directory with `{version}_{name}.up.surql` and `.down.surql` files
+    Migrate {
+        #[arg(long, default_value = "migrations")]
+        dir: PathBuf,
+        #[command(subcommand)]
+        command: MigrateCommand,
+    },
+    /// `#[derive(SurrealTable)]` structs of the database tables
+    Codegen {
+        /// writes to the file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        /// reads the tables from a `.surql` script instead of the database
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// every table if empty
+        #[arg(conflicts_with = "file")]
+        tables: Vec<String>,
+    },
+    /// runs `.surql` files in order, stops at the first failing statement
+    Seed {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SchemaCommand {
+    /// `.surql` script of the registered tables, needs no database
+    Export {
+        /// writes to the file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+        #[arg(long, value_enum, default_value_t = DefineStrategy::Missing)]
+        strategy: DefineStrategy,
+    },
+    /// changes between the registered tables and the database, fails if there are any
+    Diff {
+        /// applies the changes instead of failing
+        #[arg(long)]
+        apply: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MigrateCommand {
+    /// applies pending migrations
+    Up {
+        /// up to this version instead of the latest
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// reverts the last migration
+    Down {
+        /// every migration after this version instead, `0` reverts everything
+        #[arg(long)]
+        to: Option<u64>,
+    },
+    /// applied and pending migrations
+    Status,
+}
+
+/// parses the arguments and runs the command, errors are printed to stderr
+pub async fn run() -> ExitCode {
+    run_with(Cli::parse()).await
+}
+
+pub async fn run_with(cli: Cli) -> ExitCode {
+    match cli.execute().await {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+impl Cli {
+    async fn connect(&self) -> Result<Surreal<Any>> {
+        let conn = any::connect(&self.url).await?;
+
+        if let (Some(username), Some(password)) = (&self.user, &self.pass) {
+            conn.signin(Root {
+                username: username.clone(),
+                password: password.clone(),
+            })
+            .await?;
+        }
+        conn.use_ns(&self.ns).use_db(&self.db).await?;
+
+        Ok(conn)
+    }
+
+    /// [connect](Self::connect), then applies the `--migrations`
+    async fn connect_migrated(&self) -> Result<Surreal<Any>> {
+        let conn = self.connect().await?;
+
+        if let Some(dir) = &self.migrations {
+            Migrator::from_dir(dir)?.migrate(&conn).await?;
+        }
+
+        Ok(conn)
+    }
+
+    async fn execute(&self) -> Result<ExitCode> {
+        match &self.command {
+            Command::Schema(SchemaCommand::Export { out, strategy }) => {
+                let script =
+                    export_schema(&self.ns, &self.db, registered_tables(&self.path), *strategy)?;
+                write(out.as_ref(), &script)?;
+            }
+            Command::Schema(SchemaCommand::Diff { apply }) => {
+                let conn = self.connect_migrated().await?;
+
+                let diff = conn.schema_diff(registered_tables(&self.path)).await?;
+                for change in &diff.changes {
+                    let sign = match change.change {
+                        Change::Added { .. } => '+',
+                        Change::Removed { .. } => '-',
+                        Change::Changed { .. } => '~',
+                    };
+                    println!("{sign} {}", change.statement());
+                }
+
+                if *apply {
+                    conn.apply_diff(&diff).await?;
+                } else if !diff.is_empty() {
+                    return Ok(ExitCode::FAILURE);
+                }
+            }
+            Command::Migrate { dir, command } => {
+                let conn = self.connect().await?;
+                let migrator = Migrator::from_dir(dir)?;
+
+                match command {
+                    MigrateCommand::Up { to } => {
+                        let applied = match to {
+                            Some(version) => migrator.migrate_to(&conn, *version).await?,
+                            None => migrator.migrate(&conn).await?,
+                        };
+                        for version in applied {
+                            println!("applied {version}");
+                        }
+                    }
+                    MigrateCommand::Down { to } => {
+                        let reverted = match to {
+                            Some(version) => migrator.migrate_to(&conn, *version).await?,
+                            None => migrator.rollback(&conn).await?.into_iter().collect(),
+                        };
+                        for version in reverted {
+                            println!("reverted {version}");
+                        }
+                    }
+                    MigrateCommand::Status => {
+                        let applied = migrator
+                            .applied(&conn)
+                            .await?
+                            .into_iter()
+                            .map(|applied| (applied.version, applied))
+                            .collect::<BTreeMap<_, _>>();
+
+                        for migration in migrator.migrations() {
+                            let status = match applied.get(&migration.version) {
+                                None => "pending",
+                                Some(applied) if applied.checksum != migration.checksum() => {
+                                    "changed"
+                                }
+                                Some(_) => "applied",
+                            };
+                            println!("{status:8} {} {}", migration.version, migration.name);
+                        }
+                        for applied in applied.values().filter(|applied| {
+                            migrator
+                                .migrations()
+                                .all(|migration| migration.version != applied.version)
+                        }) {
+                            println!("{:8} {} {}", "unknown", applied.version, applied.name);
+                        }
+                    }
+                }
+            }
+            Command::Codegen { out, file, tables } => {
+                let code = match file {
+                    Some(file) => codegen::from_surql(&std::fs::read_to_string(file)?)?,
+                    None => {
+                        let tables = tables.iter().map(String::as_str).collect::<Vec<_>>();
+                        codegen::from_db(&self.connect_migrated().await?, &tables).await?
+                    }
+                };
+                write(out.as_ref(), &code)?;
+            }
+            Command::Seed { files } => {
+                let conn = self.connect_migrated().await?;
+
+                for file in files {
+                    let sql = std::fs::read_to_string(file)?;
+                    let mut res = conn.query(sql.as_str()).await?;
+                    if let Some((ind, err)) = first_error(&mut res) {
+                        return Err(seed_error(&sql, ind, &err, file).into());
+                    }
+                    println!("seeded {}", file.display());
+                }
+            }
+        }
+
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
+/// names statement `ind` of a seed file
+fn seed_error(sql: &str, ind: usize, err: &surrealdb::Error, file: &Path) -> String {
+    match statements(sql).get(ind).map(|statement| statement.trim()) {
+        Some(statement) => format!(
+            "{} statement {ind} failed: {err}\n  {statement}",
+            file.display()
+        ),
+        None => format!("{} statement {ind} failed: {err}", file.display()),
+    }
+}
+
+fn write(out: Option<&PathBuf>, content: &str) -> Result<()> {
+    match out {
+        Some(out) => std::fs::write(out, content)?,
+        None => print!("{content}"),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use clap::Parser;
+
+    use super::*;
+
+    /// directory with a migration defining a strict `person` table and a `seed.surql`
+    fn files(name: &str, seed: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "surrealdb-extras-cli-{name}-{}",
+            std::process::id()
+        ));
+        let migrations = dir.join("migrations");
+        std::fs::create_dir_all(&migrations).unwrap();
+        std::fs::write(
+            migrations.join("1_person.up.surql"),
+            "DEFINE TABLE person SCHEMAFULL; DEFINE FIELD name ON person TYPE string;",
+        )
+        .unwrap();
+        std::fs::write(dir.join("seed.surql"), seed).unwrap();
+
+        dir
+    }
+
+    fn cli(dir: &Path) -> Cli {
+        Cli::try_parse_from([
+            "surrealdb-extras".as_ref(),
+            "--url".as_ref(),
+            "mem://".as_ref(),
+            "--migrations".as_ref(),
+            dir.join("migrations").as_os_str(),
+            "seed".as_ref(),
+            dir.join("seed.surql").as_os_str(),
+        ])
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn seed_after_migrations() {
+        let dir = files(
+            "seed",
+            "BEGIN TRANSACTION;\nCREATE person:a SET name = 'a';\nCOMMIT TRANSACTION;",
+        );
+
+        assert_eq!(run_with(cli(&dir)).await, ExitCode::SUCCESS);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn seed_names_failing_statement() {
+        let dir = files(
+            "seed-error",
+            "BEGIN;\nCREATE person:a SET name = 'a';\nCREATE person:b SET name = 1;\nCOMMIT;",
+        );
+
+        let err = cli(&dir).execute().await.unwrap_err().to_string();
+        assert!(err.contains("statement 2 failed"), "{err}");
+        assert!(err.contains("CREATE person:b SET name = 1"), "{err}");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn codegen_file_rejects_tables() {
+        assert!(
+            Cli::try_parse_from(["surrealdb-extras", "codegen", "--file", "a.surql", "person"])
+                .is_err()
+        );
+    }
+}
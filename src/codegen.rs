@@ -0,0 +1,732 @@
+//! Rust structs of an existing schema, the inverse of `#[derive(SurrealTable)]`
+//! ```ignore
+//! // build.rs
+//! let schema = std::fs::read_to_string("schema.surql")?;
+//! let code = surrealdb_extras::codegen::from_surql(&schema)?;
+//! std::fs::write(Path::new(&std::env::var("OUT_DIR")?).join("tables.rs"), code)?;
+//!
+//! // src/tables.rs
+//! include!(concat!(env!("OUT_DIR"), "/tables.rs"));
+//! ```
+//! definitions the attributes can't express, e.g. `SCHEMAFULL` tables, `DEFAULT` fields
+//! or `HNSW` indexes, are kept as `#[table(sql("DEFINE .. OVERWRITE .."))]`
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::{Display, Formatter},
+};
+
+use surrealdb::{Connection, Surreal};
+use surrealdb_core::syn::{lexer::Lexer, token::TokenKind};
+
+use crate::{
+    DefineStrategy, SurrealExt,
+    info::{DbInfo, InfoError, TableInfo},
+    schema::{
+        DefineKind, Definition, EventDef, FieldDef, IndexDef, Permission, Permissions, RelationDef,
+        TableDef,
+    },
+};
+
+/// structs of the `tables` of the current database, every table if empty
+pub async fn from_db<C: Connection>(
+    conn: &Surreal<C>,
+    tables: &[&str],
+) -> Result<String, InfoError> {
+    let db = conn.info_db().await?;
+
+    let mut infos = BTreeMap::new();
+    for table in db.tables.keys() {
+        if tables.is_empty() || tables.contains(&table.as_str()) {
+            infos.insert(table.clone(), conn.info_table(table).await?);
+        }
+    }
+
+    Ok(render(&db, &infos))
+}
+
+/// structs of the tables a SurrealQL script defines, e.g. one of [export_schema](crate::schema::export_schema)
+pub fn from_surql(sql: &str) -> surrealdb::Result<String> {
+    let mut db = DbInfo::default();
+    let mut tables = BTreeMap::<String, TableInfo>::new();
+
+    for def in Definition::parse(sql)? {
+        let info = tables.entry(def.table).or_default();
+        match def.kind {
+            DefineKind::Table => db.tables.insert(def.name, def.sql),
+            DefineKind::Field => info.fields.insert(def.name, def.sql),
+            DefineKind::Index => info.indexes.insert(def.name, def.sql),
+            DefineKind::Event => info.events.insert(def.name, def.sql),
+        };
+    }
+
+    Ok(render(&db, &tables))
+}
+
+/// structs of the `tables` defined in `db`, tables starting with `_` are skipped
+pub fn render(db: &DbInfo, tables: &BTreeMap<String, TableInfo>) -> String {
+    let generated = tables
+        .keys()
+        .filter(|table| {
+            !table.starts_with('_') && is_ident(table) && db.tables.contains_key(*table)
+        })
+        .map(String::as_str)
+        .collect::<BTreeSet<_>>();
+
+    // the `SurrealValue` derive needs the trait in scope
+    let mut res =
+        "// generated by surrealdb-extras\n\nuse surrealdb::types::SurrealValue;\n".to_string();
+    for table in &generated {
+        let statement = &db.tables[*table];
+        res.push_str(&format!(
+            "\n{}",
+            Table::new(table, statement, &tables[*table], &generated)
+        ));
+    }
+
+    res
+}
+
+/// struct of a table with its attributes
+struct Table {
+    name: String,
+    attrs: Vec<String>,
+    fields: Vec<Field>,
+    /// statements the attributes can't express
+    sql: Vec<String>,
+}
+
+struct Field {
+    name: String,
+    ty: String,
+    opts: Vec<String>,
+}
+
+impl Table {
+    fn new(name: &str, statement: &str, info: &TableInfo, generated: &BTreeSet<&str>) -> Self {
+        let mut res = Self {
+            name: name.to_string(),
+            attrs: vec![format!("db = {name}")],
+            fields: vec![],
+            sql: vec![],
+        };
+
+        let mut fields = info.fields.clone();
+        // `in` and `out` are implicit, e.g. in a `.surql` script
+        if let Some(relation) = res.table(statement) {
+            for (field, tables) in [("in", relation.from), ("out", relation.to)] {
+                fields.entry(field.to_string()).or_insert_with(|| {
+                    let kind = match tables.is_empty() {
+                        true => "record".to_string(),
+                        false => format!("record<{}>", tables.join(" | ")),
+                    };
+                    format!("DEFINE FIELD {field} ON {name} TYPE {kind}")
+                });
+            }
+        }
+        for (field, statement) in &fields {
+            res.field(field, statement, generated);
+        }
+        for (index, statement) in &info.indexes {
+            res.index(index, statement);
+        }
+        for (event, statement) in &info.events {
+            res.event(event, statement);
+        }
+
+        res
+    }
+
+    /// keeps `statement` as raw `sql` if `rendered` defines something else
+    fn check(&mut self, rendered: &str, statement: &str) -> bool {
+        let same =
+            normalize(rendered).is_some_and(|rendered| Some(rendered) == normalize(statement));
+        if !same {
+            self.sql.push(DefineStrategy::Overwrite.apply(statement));
+        }
+
+        same
+    }
+
+    /// returns the relation of a relation table
+    fn table(&mut self, statement: &str) -> Option<RelationDef> {
+        let mut def = TableDef {
+            name: self.name.clone(),
+            ..Default::default()
+        };
+
+        for (keyword, value) in clauses(statement, &TABLE_KEYWORDS) {
+            match keyword {
+                "TYPE" => {
+                    if let Some(relation) = value.strip_prefix("RELATION") {
+                        let mut res = RelationDef::default();
+                        for (keyword, value) in clauses(relation, &["IN", "OUT", "ENFORCED"]) {
+                            let tables = value.split('|').map(|table| table.trim().to_string());
+                            match keyword {
+                                "IN" => res.from = tables.collect(),
+                                "OUT" => res.to = tables.collect(),
+                                _ => res.enforced = true,
+                            }
+                        }
+                        def.relation = Some(res);
+                    }
+                }
+                "AS" => def.view = Some(value.to_string()),
+                "PERMISSIONS" => {
+                    def.permissions = permissions(value).filter(|p| *p != Permissions::none())
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(relation) = &def.relation {
+            let mut attr = vec![];
+            if !relation.from.is_empty() {
+                attr.push(format!("from = {:?}", relation.from.join(" | ")));
+            }
+            if !relation.to.is_empty() {
+                attr.push(format!("to = {:?}", relation.to.join(" | ")));
+            }
+            if relation.enforced {
+                attr.push("enforced".to_string());
+            }
+            self.attrs.push(format!("relation({})", attr.join(", ")));
+        }
+        if let Some(view) = &def.view {
+            self.attrs.push(format!("view = {view:?}"));
+        }
+        if let Some(permissions) = &def.permissions {
+            self.attrs.push(permissions_attr(permissions));
+        }
+
+        self.check(&def.statements()[0], statement);
+
+        def.relation
+    }
+
+    fn field(&mut self, name: &str, statement: &str, generated: &BTreeSet<&str>) {
+        // `tags.*` of `array<T>` is defined by the database
+        if name.ends_with(".*") || name.ends_with("[*]") {
+            return;
+        }
+        if !is_ident(name) || ["self", "Self", "super", "crate"].contains(&name) {
+            self.sql.push(DefineStrategy::Overwrite.apply(statement));
+            return;
+        }
+
+        let clauses = clauses(statement, &FIELD_KEYWORDS);
+        let clause = |keyword| {
+            clauses
+                .iter()
+                .find(|(clause, _)| *clause == keyword)
+                .map(|(_, value)| *value)
+        };
+
+        let kind = clause("TYPE").unwrap_or("any");
+        let mut ty = rust_type(kind, generated);
+        let mut opts = vec![];
+
+        if let Some(record) = &ty.record {
+            opts.push(format!("record = {record}"));
+        }
+        // e.g. `datetime`, the derive only knows `DateTime`
+        if normalize_kind(&ty.kind) != normalize_kind(kind) && is_ident(kind) {
+            opts.push(format!("db_type = {kind}"));
+            ty.kind = kind.to_string();
+        }
+
+        let def = FieldDef {
+            name: name.to_string(),
+            kind: ty.kind,
+            permissions: clause("PERMISSIONS")
+                .and_then(permissions)
+                .filter(|p| *p != Permissions::full()),
+        };
+
+        if let Some(permissions) = &def.permissions {
+            opts.push(permissions_attr(permissions));
+        }
+
+        self.check(&def.statement(&self.name), statement);
+        self.fields.push(Field {
+            name: name.to_string(),
+            ty: ty.ty,
+            opts,
+        });
+    }
+
+    fn index(&mut self, name: &str, statement: &str) {
+        let clauses = clauses(statement, &INDEX_KEYWORDS);
+        let def = IndexDef {
+            name: name.to_string(),
+            fields: clauses
+                .iter()
+                .find(|(keyword, _)| *keyword == "FIELDS")
+                .map(|(_, fields)| fields.split(',').map(|f| f.trim().to_string()).collect())
+                .unwrap_or_default(),
+            unique: clauses.iter().any(|(keyword, _)| *keyword == "UNIQUE"),
+        };
+
+        if is_ident(name) && self.check(&def.statement(&self.name), statement) {
+            self.attrs.push(format!(
+                "index(name = {name}, fields = {:?}{})",
+                def.fields.join(", "),
+                if def.unique { ", unique" } else { "" }
+            ));
+        }
+    }
+
+    fn event(&mut self, name: &str, statement: &str) {
+        let clauses = clauses(statement, &["WHEN", "THEN", "COMMENT"]);
+        let clause = |keyword| {
+            clauses
+                .iter()
+                .find(|(clause, _)| *clause == keyword)
+                .map(|(_, value)| value.to_string())
+        };
+        let def = EventDef {
+            name: name.to_string(),
+            when: clause("WHEN").filter(|when| when != "true"),
+            then: clause("THEN").unwrap_or_default(),
+        };
+
+        if is_ident(name) && self.check(&def.statement(&self.name), statement) {
+            let mut attr = format!("event(name = {name}");
+            if let Some(when) = &def.when {
+                attr.push_str(&format!(", when = {when:?}"));
+            }
+            attr.push_str(&format!(", then = {:?})", def.then));
+            self.attrs.push(attr);
+        }
+    }
+}
+
+impl Display for Table {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self {
+            name,
+            attrs,
+            fields,
+            sql,
+        } = self;
+
+        writeln!(
+            f,
+            "#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, \
+            SurrealValue, surrealdb_extras::SurrealTable)]"
+        )?;
+        writeln!(f, "#[table(")?;
+        for attr in attrs {
+            writeln!(f, "    {attr},")?;
+        }
+        if !sql.is_empty() {
+            writeln!(f, "    sql(")?;
+            for statement in sql {
+                writeln!(f, "        {statement:?},")?;
+            }
+            writeln!(f, "    ),")?;
+        }
+        writeln!(f, ")]")?;
+
+        if fields.is_empty() {
+            return writeln!(f, "pub struct {} {{}}", struct_name(name));
+        }
+
+        writeln!(f, "pub struct {} {{", struct_name(name))?;
+        for Field { name, ty, opts } in fields {
+            if !opts.is_empty() {
+                writeln!(f, "    #[opt({})]", opts.join(", "))?;
+            }
+            match KEYWORDS.contains(&name.as_str()) {
+                true => {
+                    writeln!(f, "    #[surreal(rename = {name:?})]")?;
+                    writeln!(f, "    pub r#{name}: {ty},")?;
+                }
+                false => writeln!(f, "    pub {name}: {ty},")?,
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+const TABLE_KEYWORDS: [&str; 8] = [
+    "TYPE",
+    "DROP",
+    "SCHEMAFULL",
+    "SCHEMALESS",
+    "AS",
+    "CHANGEFEED",
+    "PERMISSIONS",
+    "COMMENT",
+];
+
+const FIELD_KEYWORDS: [&str; 10] = [
+    "TYPE",
+    "FLEXIBLE",
+    "REFERENCE",
+    "DEFAULT",
+    "READONLY",
+    "VALUE",
+    "ASSERT",
+    "COMPUTED",
+    "PERMISSIONS",
+    "COMMENT",
+];
+
+const INDEX_KEYWORDS: [&str; 10] = [
+    "FIELDS",
+    "COLUMNS",
+    "UNIQUE",
+    "COUNT",
+    "SEARCH",
+    "FULLTEXT",
+    "HNSW",
+    "MTREE",
+    "COMMENT",
+    "CONCURRENTLY",
+];
+
+/// field names that need `r#`
+const KEYWORDS: [&str; 48] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// clauses of a normalized statement, e.g. `[("TYPE", "string"), ("READONLY", "")]`,
+/// each keyword token outside of blocks starts one clause
+fn clauses<'a>(sql: &'a str, keywords: &[&'static str]) -> Vec<(&'static str, &'a str)> {
+    let mut res: Vec<(&'static str, usize)> = vec![];
+    let mut ends = vec![];
+    let mut depth = 0usize;
+
+    for token in Lexer::new(sql.as_bytes()) {
+        let start = token.span.offset as usize;
+        let end = start + token.span.len as usize;
+        match token.kind {
+            TokenKind::OpenDelim(_) => depth += 1,
+            TokenKind::CloseDelim(_) => depth = depth.saturating_sub(1),
+            TokenKind::Keyword(_) | TokenKind::Identifier if depth == 0 => {
+                let keyword = keywords.iter().find(|keyword| {
+                    **keyword == &sql[start..end] && res.iter().all(|(used, _)| used != *keyword)
+                });
+                if let Some(keyword) = keyword {
+                    ends.push(start);
+                    res.push((keyword, end));
+                }
+            }
+            _ => {}
+        }
+    }
+    ends.push(sql.len());
+
+    res.into_iter()
+        .zip(ends.into_iter().skip(1))
+        .map(|((keyword, start), end)| (keyword, sql[start..end].trim()))
+        .collect()
+}
+
+/// `NONE`, `FULL` or `FOR select, create FULL, FOR update WHERE ..`, missing ones are `NONE`
+fn permissions(sql: &str) -> Option<Permissions> {
+    match sql.trim() {
+        "NONE" => return Some(Permissions::none()),
+        "FULL" => return Some(Permissions::full()),
+        _ => {}
+    }
+
+    let mut res = Permissions::none();
+    for group in sql.split("FOR ").filter(|group| !group.trim().is_empty()) {
+        let group = group.trim().trim_end_matches(',');
+        let mut ops = vec![];
+        let mut rest = group;
+        while let Some((op, tail)) = rest.split_once(char::is_whitespace) {
+            let op = op.trim_end_matches(',');
+            if !["select", "create", "update", "delete"].contains(&op) {
+                break;
+            }
+            ops.push(op);
+            rest = tail.trim_start();
+        }
+
+        let permission = Permission::parse(rest);
+        for op in ops {
+            match op {
+                "select" => res.select = permission.clone(),
+                "create" => res.create = permission.clone(),
+                "update" => res.update = permission.clone(),
+                _ => res.delete = permission.clone(),
+            }
+        }
+    }
+
+    Some(res)
+}
+
+/// `permissions(select = "FULL", ..)` without the `NONE` ones
+fn permissions_attr(permissions: &Permissions) -> String {
+    let Permissions {
+        select,
+        create,
+        update,
+        delete,
+    } = permissions;
+
+    let attr = [
+        ("select", select),
+        ("create", create),
+        ("update", update),
+        ("delete", delete),
+    ]
+    .into_iter()
+    .filter(|(_, permission)| **permission != Permission::None)
+    .map(|(op, permission)| format!("{op} = {:?}", permission.to_string()))
+    .collect::<Vec<_>>();
+
+    format!("permissions({})", attr.join(", "))
+}
+
+/// normalized statement without the create strategy
+fn normalize(sql: &str) -> Option<String> {
+    Definition::parse(sql).ok()?.pop().map(|def| def.sql)
+}
+
+fn normalize_kind(kind: &str) -> Option<String> {
+    normalize(&format!("DEFINE FIELD field ON kind TYPE {kind}"))
+}
+
+fn is_ident(name: &str) -> bool {
+    name.chars()
+        .next()
+        .is_some_and(|first| !first.is_ascii_digit())
+        && name
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// `person_info` -> `PersonInfo`
+fn struct_name(table: &str) -> String {
+    table
+        .split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Rust type of a field and the `kind` the derive renders for it
+struct RustType {
+    ty: String,
+    kind: String,
+    /// `#[opt(record = ..)]`
+    record: Option<String>,
+}
+
+impl RustType {
+    fn new(ty: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            ty: ty.into(),
+            kind: kind.into(),
+            record: None,
+        }
+    }
+
+    fn wrap(self, ty: &str, kind: &str) -> Self {
+        Self {
+            ty: format!("{ty}<{}>", self.ty),
+            kind: format!("{kind}<{}>", self.kind),
+            record: self.record,
+        }
+    }
+}
+
+/// splits `kind` at `separator` outside of `<..>`
+fn split_top(kind: &str, separator: char) -> Vec<&str> {
+    let mut res = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+
+    for (ind, char) in kind.char_indices() {
+        match char {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            char if char == separator && depth == 0 => {
+                res.push(kind[start..ind].trim());
+                start = ind + 1;
+            }
+            _ => {}
+        }
+    }
+    res.push(kind[start..].trim());
+
+    res
+}
+
+fn rust_type(kind: &str, generated: &BTreeSet<&str>) -> RustType {
+    let variants = split_top(kind, '|');
+    let optional = variants
+        .iter()
+        .any(|variant| ["none", "null"].contains(variant));
+    let variants = variants
+        .into_iter()
+        .filter(|variant| !["none", "null"].contains(variant))
+        .collect::<Vec<_>>();
+
+    let res = match variants.as_slice() {
+        [kind] => {
+            let (name, arg) = match kind.split_once('<') {
+                Some((name, arg)) => (name, arg.strip_suffix('>').unwrap_or(arg)),
+                None => (*kind, ""),
+            };
+            let inner = || rust_type(split_top(arg, ',')[0], generated);
+
+            match name {
+                "option" => inner().wrap("Option", "option"),
+                "array" | "set" if !arg.is_empty() => inner().wrap("Vec", "array"),
+                "array" | "set" => RustType::new("Vec<surrealdb::types::Value>", "array<object>"),
+                "record" if is_ident(arg) => RustType {
+                    ty: match generated.contains(arg) {
+                        true => format!("surrealdb_extras::RecordIdType<{}>", struct_name(arg)),
+                        false => "surrealdb::types::RecordId".to_string(),
+                    },
+                    kind: format!("record<{arg}>"),
+                    record: Some(arg.to_string()),
+                },
+                "record" => RustType::new("surrealdb::types::RecordId", "record"),
+                "string" => RustType::new("String", "string"),
+                "int" => RustType::new("i64", "int"),
+                "float" => RustType::new("f64", "float"),
+                "bool" => RustType::new("bool", "bool"),
+                "bytes" => RustType::new("Vec<u8>", "bytes"),
+                "number" => RustType::new("surrealdb::types::Number", "number"),
+                "decimal" => RustType::new("surrealdb::types::Decimal", "decimal"),
+                "datetime" => RustType::new("surrealdb::types::Datetime", "datetime"),
+                "duration" => RustType::new("surrealdb::types::Duration", "duration"),
+                "uuid" => RustType::new("surrealdb::types::Uuid", "uuid"),
+                _ => RustType::new("surrealdb::types::Value", "object"),
+            }
+        }
+        _ => RustType::new("surrealdb::types::Value", "object"),
+    };
+
+    match optional {
+        true => res.wrap("Option", "option"),
+        false => res,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clauses_of_field() {
+        let sql = "DEFINE FIELD name ON person TYPE string \
+            DEFAULT 'TYPE ASSERT' ASSERT $value != NONE PERMISSIONS FULL";
+
+        assert_eq!(
+            clauses(sql, &FIELD_KEYWORDS),
+            [
+                ("TYPE", "string"),
+                ("DEFAULT", "'TYPE ASSERT'"),
+                ("ASSERT", "$value != NONE"),
+                ("PERMISSIONS", "FULL"),
+            ]
+        );
+    }
+
+    #[test]
+    fn clauses_skip_blocks() {
+        let sql = "DEFINE FIELD total ON person TYPE int \
+            VALUE (SELECT VALUE count FROM stats LIMIT 1)[0] READONLY";
+
+        assert_eq!(
+            clauses(sql, &FIELD_KEYWORDS),
+            [
+                ("TYPE", "int"),
+                ("VALUE", "(SELECT VALUE count FROM stats LIMIT 1)[0]"),
+                ("READONLY", ""),
+            ]
+        );
+    }
+
+    #[test]
+    fn rust_types() {
+        let generated = BTreeSet::from(["person"]);
+        let ty = |kind| {
+            let RustType { ty, kind, record } = rust_type(kind, &generated);
+            (ty, kind, record)
+        };
+
+        assert_eq!(ty("string"), ("String".into(), "string".into(), None));
+        assert_eq!(
+            ty("option<datetime>"),
+            (
+                "Option<surrealdb::types::Datetime>".into(),
+                "option<datetime>".into(),
+                None
+            )
+        );
+        assert_eq!(
+            ty("decimal | none"),
+            (
+                "Option<surrealdb::types::Decimal>".into(),
+                "option<decimal>".into(),
+                None
+            )
+        );
+        assert_eq!(
+            ty("array<uuid>"),
+            (
+                "Vec<surrealdb::types::Uuid>".into(),
+                "array<uuid>".into(),
+                None
+            )
+        );
+        assert_eq!(
+            ty("record<person>"),
+            (
+                "surrealdb_extras::RecordIdType<Person>".into(),
+                "record<person>".into(),
+                Some("person".into())
+            )
+        );
+        assert_eq!(
+            ty("record<company>").0,
+            "surrealdb::types::RecordId".to_string()
+        );
+        assert_eq!(ty("string | int").0, "surrealdb::types::Value".to_string());
+    }
+
+    #[test]
+    fn from_surql_attributes() {
+        let code = from_surql(
+            "DEFINE TABLE person SCHEMAFULL;
+            DEFINE FIELD name ON person TYPE string;
+            DEFINE FIELD created ON person TYPE datetime DEFAULT time::now();
+            DEFINE INDEX person_name ON person FIELDS name UNIQUE;
+            DEFINE TABLE _migrations;",
+        )
+        .unwrap();
+
+        assert!(code.contains("pub struct Person {"), "{code}");
+        assert!(code.contains("pub name: String,"), "{code}");
+        assert!(
+            code.contains("pub created: surrealdb::types::Datetime,"),
+            "{code}"
+        );
+        assert!(
+            code.contains("index(name = person_name, fields = \"name\", unique)"),
+            "{code}"
+        );
+        assert!(code.contains("DEFINE TABLE OVERWRITE person"), "{code}");
+        assert!(code.contains("DEFINE FIELD OVERWRITE created"), "{code}");
+        assert!(!code.contains("Migrations"), "{code}");
+    }
+}
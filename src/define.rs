@@ -2,10 +2,18 @@ use std::collections::HashSet;
 
 use surrealdb::{Connection, Surreal, types::Value};
 
-use crate::surreal_table::Register;
+use crate::{
+    info::{DbInfo, InfoError, KvInfo, NsInfo, Sections, TableInfo},
+    query::first_error,
+    schema::{Definition, SchemaDiff, define_order},
+    setup::{DefineStrategy, SetupOptions, log_statement},
+    surreal_table::Register,
+    transaction::Transaction,
+};
 
 pub trait SurrealExt {
-    /// creates namespace, db, tables and defines the attributes if they do not exist
+    /// creates namespace, db, tables and defines the attributes if they do not exist,
+    /// tables are defined after the tables they depend on, see [define_order]
     fn use_ns_db_checked(
         &self,
         ns: impl AsRef<str>,
@@ -13,12 +21,71 @@ pub trait SurrealExt {
         register: impl IntoIterator<Item = Register>,
     ) -> impl std::future::Future<Output = surrealdb::Result<()>>;
 
+    /// [use_ns_db_checked](Self::use_ns_db_checked) with [SetupOptions],
+    /// returns the statements it ran or would run on a dry run
+    fn setup(
+        &self,
+        ns: impl AsRef<str>,
+        db: impl AsRef<str>,
+        register: impl IntoIterator<Item = Register>,
+        options: SetupOptions,
+    ) -> impl std::future::Future<Output = surrealdb::Result<Vec<String>>>;
+
+    /// `key_value.1` is not defined in section `key_value.0` of the `INFO FOR` query,
+    /// e.g. `("INFO FOR KV", ("namespaces", ns))`
     fn missing(
         &self,
         query: &str,
         key_value: (&str, &str),
-    ) -> impl std::future::Future<Output = bool> + Send;
-    fn table_list(&self) -> impl std::future::Future<Output = HashSet<String>> + Send;
+    ) -> impl std::future::Future<Output = Result<bool, InfoError>> + Send;
+    /// tables of the current database
+    fn table_list(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashSet<String>, InfoError>> + Send;
+
+    fn info_kv(&self) -> impl std::future::Future<Output = Result<KvInfo, InfoError>> + Send;
+    fn info_ns(&self) -> impl std::future::Future<Output = Result<NsInfo, InfoError>> + Send;
+    fn info_db(&self) -> impl std::future::Future<Output = Result<DbInfo, InfoError>> + Send;
+    fn info_table(
+        &self,
+        table: &str,
+    ) -> impl std::future::Future<Output = Result<TableInfo, InfoError>> + Send;
+
+    /// [use_ns_db_checked](Self::use_ns_db_checked) with every derived table in the binary,
+    /// then runs the [register_sql](crate::register_sql) statements
+    #[cfg(feature = "inventory")]
+    fn define_all(
+        &self,
+        ns: impl AsRef<str>,
+        db: impl AsRef<str>,
+    ) -> impl std::future::Future<Output = surrealdb::Result<()>>;
+
+    /// [define_all](Self::define_all) limited to the tables and statements in module `path`
+    /// (e.g. `my_app::models`) and its submodules
+    #[cfg(feature = "inventory")]
+    fn define_all_in(
+        &self,
+        ns: impl AsRef<str>,
+        db: impl AsRef<str>,
+        path: &str,
+    ) -> impl std::future::Future<Output = surrealdb::Result<()>>;
+
+    /// starts a transaction, see [Transaction]
+    fn transaction(&self) -> Transaction<'_, Self>
+    where
+        Self: Sized;
+
+    /// compares what the registered tables define with `INFO FOR TABLE`
+    fn schema_diff(
+        &self,
+        register: impl IntoIterator<Item = Register>,
+    ) -> impl std::future::Future<Output = surrealdb::Result<SchemaDiff>>;
+
+    /// runs the [statements](SchemaDiff::statements) of the diff in one transaction
+    fn apply_diff(
+        &self,
+        diff: &SchemaDiff,
+    ) -> impl std::future::Future<Output = surrealdb::Result<()>>;
 }
 
 impl<C> SurrealExt for Surreal<C>
@@ -31,71 +98,233 @@ where
         db: impl AsRef<str>,
         register: impl IntoIterator<Item = Register>,
     ) -> surrealdb::Result<()> {
+        self.setup(ns, db, register, SetupOptions::default())
+            .await
+            .map(|_| ())
+    }
+
+    async fn setup(
+        &self,
+        ns: impl AsRef<str>,
+        db: impl AsRef<str>,
+        register: impl IntoIterator<Item = Register>,
+        options: SetupOptions,
+    ) -> surrealdb::Result<Vec<String>> {
         let ns = ns.as_ref();
         let db = db.as_ref();
+        let mut statements = vec![];
+
+        // on a dry run nothing below a missing namespace or database exists yet,
+        // `use_ns`/`use_db` would create them
+        let mut exists = self.info_kv().await?.namespaces.contains_key(ns);
+        if !exists {
+            run_setup(
+                self,
+                format!("DEFINE NAMESPACE {ns}"),
+                options,
+                &mut statements,
+            )
+            .await?;
+        }
 
-        if self.missing("INFO FOR KV", ("namespaces", ns)).await {
-            self.query(format!("DEFINE NAMESPACE {ns};")).await?;
+        if exists || !options.dry_run {
+            self.use_ns(ns).await?;
         }
 
-        self.use_ns(ns).await?;
+        exists = exists && self.info_ns().await?.databases.contains_key(db);
+        if !exists {
+            run_setup(
+                self,
+                format!("DEFINE DATABASE {db}"),
+                options,
+                &mut statements,
+            )
+            .await?;
+        }
 
-        if self.missing("INFO FOR NS", ("databases", db)).await {
-            self.query(format!("DEFINE DATABASE {db};")).await?;
+        if exists || !options.dry_run {
+            self.use_db(db).await?;
         }
 
-        self.use_db(db).await?;
+        let tables = match exists || !options.dry_run {
+            true => self.table_list().await?,
+            false => HashSet::new(),
+        };
+
+        for register in define_order(register, &tables)? {
+            if options.strategy == DefineStrategy::Missing && tables.contains((register.name)()) {
+                continue;
+            }
+
+            for statement in register.funcs() {
+                let statement = options.strategy.apply(&statement);
+                run_setup(self, statement, options, &mut statements).await?;
+            }
+        }
 
-        let tables = self.table_list().await;
+        Ok(statements)
+    }
+
+    #[cfg(feature = "inventory")]
+    async fn define_all(&self, ns: impl AsRef<str>, db: impl AsRef<str>) -> surrealdb::Result<()> {
+        self.define_all_in(ns, db, "").await
+    }
+
+    #[cfg(feature = "inventory")]
+    async fn define_all_in(
+        &self,
+        ns: impl AsRef<str>,
+        db: impl AsRef<str>,
+        path: &str,
+    ) -> surrealdb::Result<()> {
+        self.use_ns_db_checked(ns, db, crate::registry::registered_tables(path))
+            .await?;
 
-        for (name, _, funcs) in register {
-            if !tables.contains(name()) {
-                for query in funcs() {
-                    println!("{query}");
-                    self.query(query).await?;
-                }
+        for register in crate::registry::registered_sql(path) {
+            let mut res = self.query(register.sql).await?;
+            if let Some((_, err)) = first_error(&mut res) {
+                return Err(surrealdb::Error::Query(format!(
+                    "{} ({}) failed: {err}",
+                    register.sql, register.path
+                )));
             }
         }
 
         Ok(())
     }
 
-    async fn missing(&self, query: &str, key_value: (&str, &str)) -> bool {
-        self.query(query)
-            .await
-            .unwrap()
-            .take::<Value>(0)
-            .unwrap()
-            .into_json_value()
-            .as_object()
-            .unwrap()
-            .get_key_value(key_value.0)
-            .unwrap()
-            .1
-            .clone()
-            .as_object()
-            .unwrap()
-            .get_key_value(key_value.1)
-            .is_none()
+    async fn missing(&self, query: &str, key_value: (&str, &str)) -> Result<bool, InfoError> {
+        let value = self.query(query).await?.take::<Value>(0)?;
+        let definitions = Sections::new(query, value)?.take(key_value.0)?;
+
+        Ok(!definitions.contains_key(key_value.1))
     }
 
-    async fn table_list(&self) -> HashSet<String> {
-        self.query("INFO FOR DB")
-            .await
-            .unwrap()
-            .take::<Value>(0)
-            .unwrap()
-            .into_json_value()
-            .as_object()
-            .unwrap()
-            .get_key_value("tables")
-            .unwrap()
-            .1
-            .clone()
-            .as_object()
-            .unwrap()
-            .keys()
-            .cloned()
-            .collect::<HashSet<_>>()
+    async fn table_list(&self) -> Result<HashSet<String>, InfoError> {
+        Ok(self.info_db().await?.tables.into_keys().collect())
+    }
+
+    async fn info_kv(&self) -> Result<KvInfo, InfoError> {
+        KvInfo::from_value(self.query("INFO FOR KV").await?.take(0)?)
+    }
+
+    async fn info_ns(&self) -> Result<NsInfo, InfoError> {
+        NsInfo::from_value(self.query("INFO FOR NS").await?.take(0)?)
+    }
+
+    async fn info_db(&self) -> Result<DbInfo, InfoError> {
+        DbInfo::from_value(self.query("INFO FOR DB").await?.take(0)?)
     }
+
+    async fn info_table(&self, table: &str) -> Result<TableInfo, InfoError> {
+        TableInfo::from_value(
+            self.query(format!("INFO FOR TABLE {table}"))
+                .await?
+                .take(0)?,
+        )
+    }
+
+    fn transaction(&self) -> Transaction<'_, Self> {
+        Transaction::new(self)
+    }
+
+    async fn schema_diff(
+        &self,
+        register: impl IntoIterator<Item = Register>,
+    ) -> surrealdb::Result<SchemaDiff> {
+        let mut tables = vec![];
+        let mut expected = vec![];
+
+        for register in register {
+            tables.push((register.name)());
+            for query in register.funcs() {
+                expected.extend(Definition::parse(&query)?);
+            }
+        }
+        expected.retain(|def| tables.contains(&def.table.as_str()));
+
+        let existing = self.table_list().await?;
+        let tables = tables
+            .into_iter()
+            .filter(|table| existing.contains(*table))
+            .collect::<Vec<_>>();
+
+        let mut query = "INFO FOR DB;\n".to_string();
+        for table in &tables {
+            query.push_str(&format!("INFO FOR TABLE {table};\n"));
+        }
+        let mut res = self.query(query).await?;
+
+        let mut db = DbInfo::from_value(res.take(0)?)?;
+        let mut current = vec![];
+
+        for (ind, table) in tables.into_iter().enumerate() {
+            let TableInfo {
+                fields,
+                indexes,
+                events,
+                ..
+            } = TableInfo::from_value(res.take(ind + 1)?)?;
+
+            let definitions = db
+                .tables
+                .remove(table)
+                .into_iter()
+                .chain(fields.into_values())
+                .chain(indexes.into_values())
+                .chain(events.into_values());
+
+            for definition in definitions {
+                current.extend(Definition::parse(&definition)?);
+            }
+        }
+
+        Ok(SchemaDiff::new(expected, current))
+    }
+
+    async fn apply_diff(&self, diff: &SchemaDiff) -> surrealdb::Result<()> {
+        let statements = diff.statements();
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        let mut res = self
+            .query(format!(
+                "BEGIN TRANSACTION;\n{};\nCOMMIT TRANSACTION;",
+                statements.join(";\n")
+            ))
+            .await?;
+
+        match first_error(&mut res) {
+            Some((ind, err)) => Err(surrealdb::Error::Query(format!(
+                "{} failed: {err}",
+                statements
+                    .get(ind.wrapping_sub(1))
+                    .map_or("COMMIT", String::as_str)
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// logs the statement and runs it unless it's a dry run
+async fn run_setup<C: Connection>(
+    conn: &Surreal<C>,
+    statement: String,
+    options: SetupOptions,
+    statements: &mut Vec<String>,
+) -> surrealdb::Result<()> {
+    log_statement(&statement, options.dry_run);
+
+    if !options.dry_run {
+        let mut res = conn.query(&statement).await?;
+        if let Some((_, err)) = first_error(&mut res) {
+            return Err(surrealdb::Error::Query(format!(
+                "{statement} failed: {err}"
+            )));
+        }
+    }
+
+    statements.push(statement);
+    Ok(())
 }
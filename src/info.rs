@@ -0,0 +1,303 @@
+//! typed `INFO FOR KV/NS/DB/TABLE` output, definitions are `name -> DEFINE statement`
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+use surrealdb::types::{Object, Value};
+
+#[derive(Debug)]
+pub enum InfoError {
+    Db(surrealdb::Error),
+    /// the `INFO FOR` output has an unexpected shape, e.g. after a server update
+    Shape {
+        info: String,
+        path: String,
+        expected: &'static str,
+    },
+}
+
+impl Display for InfoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(err) => write!(f, "{err}"),
+            Self::Shape {
+                info,
+                path,
+                expected,
+            } => match path.is_empty() {
+                true => write!(f, "`{info}` did not return a `{expected}` value"),
+                false => write!(
+                    f,
+                    "`{info}` did not return a `{expected}` value at `{path}`"
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for InfoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Db(err) => Some(err),
+            Self::Shape { .. } => None,
+        }
+    }
+}
+
+impl From<surrealdb::Error> for InfoError {
+    fn from(value: surrealdb::Error) -> Self {
+        Self::Db(value)
+    }
+}
+
+impl From<InfoError> for surrealdb::Error {
+    fn from(value: InfoError) -> Self {
+        match value {
+            InfoError::Db(err) => err,
+            err => surrealdb::Error::InternalError(err.to_string()),
+        }
+    }
+}
+
+/// `INFO FOR KV`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KvInfo {
+    pub namespaces: BTreeMap<String, String>,
+    pub users: BTreeMap<String, String>,
+    pub accesses: BTreeMap<String, String>,
+}
+
+impl KvInfo {
+    pub fn from_value(value: Value) -> Result<Self, InfoError> {
+        let mut sections = Sections::new("INFO FOR KV", value)?;
+
+        Ok(Self {
+            namespaces: sections.take("namespaces")?,
+            users: sections.take("users")?,
+            accesses: sections.take("accesses")?,
+        })
+    }
+}
+
+/// `INFO FOR NS`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NsInfo {
+    pub databases: BTreeMap<String, String>,
+    pub users: BTreeMap<String, String>,
+    pub accesses: BTreeMap<String, String>,
+}
+
+impl NsInfo {
+    pub fn from_value(value: Value) -> Result<Self, InfoError> {
+        let mut sections = Sections::new("INFO FOR NS", value)?;
+
+        Ok(Self {
+            databases: sections.take("databases")?,
+            users: sections.take("users")?,
+            accesses: sections.take("accesses")?,
+        })
+    }
+}
+
+/// `INFO FOR DB`, sections not listed here are ignored
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DbInfo {
+    pub tables: BTreeMap<String, String>,
+    pub functions: BTreeMap<String, String>,
+    pub params: BTreeMap<String, String>,
+    pub analyzers: BTreeMap<String, String>,
+    pub users: BTreeMap<String, String>,
+    pub accesses: BTreeMap<String, String>,
+}
+
+impl DbInfo {
+    pub fn from_value(value: Value) -> Result<Self, InfoError> {
+        let mut sections = Sections::new("INFO FOR DB", value)?;
+
+        Ok(Self {
+            tables: sections.take("tables")?,
+            functions: sections.take("functions")?,
+            params: sections.take("params")?,
+            analyzers: sections.take("analyzers")?,
+            users: sections.take("users")?,
+            accesses: sections.take("accesses")?,
+        })
+    }
+}
+
+/// `INFO FOR TABLE`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableInfo {
+    pub fields: BTreeMap<String, String>,
+    pub indexes: BTreeMap<String, String>,
+    pub events: BTreeMap<String, String>,
+    /// views on the table
+    pub tables: BTreeMap<String, String>,
+    pub lives: BTreeMap<String, String>,
+}
+
+impl TableInfo {
+    pub fn from_value(value: Value) -> Result<Self, InfoError> {
+        let mut sections = Sections::new("INFO FOR TABLE", value)?;
+
+        Ok(Self {
+            fields: sections.take("fields")?,
+            indexes: sections.take("indexes")?,
+            events: sections.take("events")?,
+            tables: sections.take("tables")?,
+            lives: sections.take("lives")?,
+        })
+    }
+}
+
+/// top level object of an `INFO FOR` output
+pub(crate) struct Sections {
+    info: String,
+    object: Object,
+}
+
+impl Sections {
+    pub(crate) fn new(info: impl Into<String>, value: Value) -> Result<Self, InfoError> {
+        let info = info.into();
+
+        match value {
+            Value::Object(object) => Ok(Self { info, object }),
+            _ => Err(InfoError::Shape {
+                info,
+                path: String::new(),
+                expected: "object",
+            }),
+        }
+    }
+
+    /// definitions of a section, empty if the server doesn't return it
+    pub(crate) fn take(&mut self, section: &str) -> Result<BTreeMap<String, String>, InfoError> {
+        let shape = |path: String, expected| InfoError::Shape {
+            info: self.info.clone(),
+            path,
+            expected,
+        };
+
+        match self.object.remove(section) {
+            None | Some(Value::None) => Ok(BTreeMap::new()),
+            Some(Value::Object(definitions)) => definitions
+                .into_iter()
+                .map(|(name, definition)| match definition {
+                    Value::String(definition) => Ok((name, definition)),
+                    _ => Err(shape(format!("{section}.{name}"), "string")),
+                })
+                .collect(),
+            Some(_) => Err(shape(section.to_string(), "object")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::{Object, Value};
+
+    use super::*;
+    use crate::{SurrealExt, test_conn};
+
+    fn object<const N: usize>(entries: [(&str, Value); N]) -> Value {
+        let mut object = Object::new();
+        for (key, value) in entries {
+            object.insert(key, value);
+        }
+        Value::Object(object)
+    }
+
+    fn definitions<const N: usize>(entries: [(&str, &str); N]) -> Value {
+        object(entries.map(|(name, definition)| (name, Value::String(definition.to_string()))))
+    }
+
+    fn map<const N: usize>(entries: [(&str, &str); N]) -> BTreeMap<String, String> {
+        entries
+            .into_iter()
+            .map(|(name, definition)| (name.to_string(), definition.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn db_sections() {
+        let info = DbInfo::from_value(object([
+            ("tables", definitions([("person", "DEFINE TABLE person")])),
+            (
+                "functions",
+                definitions([("add", "DEFINE FUNCTION fn::add() {}")]),
+            ),
+            ("params", Value::None),
+            ("models", definitions([("m", "DEFINE MODEL ml::m<1.0.0>")])),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            info,
+            DbInfo {
+                tables: map([("person", "DEFINE TABLE person")]),
+                functions: map([("add", "DEFINE FUNCTION fn::add() {}")]),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn table_sections() {
+        let info = TableInfo::from_value(object([
+            (
+                "fields",
+                definitions([("name", "DEFINE FIELD name ON person")]),
+            ),
+            ("indexes", definitions([])),
+            ("lives", Value::None),
+        ]))
+        .unwrap();
+
+        assert_eq!(info.fields, map([("name", "DEFINE FIELD name ON person")]));
+        assert!(info.indexes.is_empty() && info.events.is_empty() && info.lives.is_empty());
+    }
+
+    #[test]
+    fn wrong_shapes() {
+        let err = DbInfo::from_value(Value::String("tables".to_string())).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`INFO FOR DB` did not return a `object` value"
+        );
+
+        let err = TableInfo::from_value(object([("fields", Value::Bool(true))])).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`INFO FOR TABLE` did not return a `object` value at `fields`"
+        );
+
+        let err = DbInfo::from_value(object([(
+            "tables",
+            object([("person", Value::Bool(true))]),
+        )]))
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`INFO FOR DB` did not return a `string` value at `tables.person`"
+        );
+    }
+
+    #[tokio::test]
+    async fn server_output() {
+        let conn = test_conn().await;
+        conn.query("DEFINE TABLE person SCHEMAFULL; DEFINE FIELD name ON person TYPE string;")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let db = conn.info_db().await.unwrap();
+        assert_eq!(db.tables.keys().collect::<Vec<_>>(), ["person"]);
+
+        let table = conn.info_table("person").await.unwrap();
+        assert_eq!(table.fields.keys().collect::<Vec<_>>(), ["name"]);
+        assert!(table.fields["name"].starts_with("DEFINE FIELD name ON person"));
+    }
+}
@@ -0,0 +1,166 @@
+use surrealdb::{
+    Error,
+    types::{Array, Object, RecordIdKey, SurrealValue, Value},
+};
+
+/// compound record id keys (`table:['a', 1]` or `table:{ a: 1 }`)
+/// will be created by proc macro for structs
+/// ```
+/// #[derive(surrealdb_extras::SurrealKey)]
+/// struct ReadingKey(String, i64);
+///
+/// #[derive(surrealdb_extras::SurrealKey)]
+/// struct TenantKey {
+///     tenant: String,
+///     id: i64,
+/// }
+/// ```
+pub trait SurrealKey: Sized {
+    /// converts into `RecordIdKey::Array` or `RecordIdKey::Object`
+    fn into_key(self) -> RecordIdKey;
+    /// parses from `RecordIdKey::Array` or `RecordIdKey::Object`
+    fn from_key(key: RecordIdKey) -> Result<Self, Error>;
+}
+
+impl SurrealKey for RecordIdKey {
+    fn into_key(self) -> RecordIdKey {
+        self
+    }
+
+    fn from_key(key: RecordIdKey) -> Result<Self, Error> {
+        Ok(key)
+    }
+}
+
+macro_rules! impl_tuple_key {
+    ($($len:literal => ($($t:ident: $i:literal),+)),+ $(,)?) => {
+        $(
+            impl<$($t: SurrealValue),+> SurrealKey for ($($t,)+) {
+                #[allow(non_snake_case)]
+                fn into_key(self) -> RecordIdKey {
+                    let ($($t,)+) = self;
+                    RecordIdKey::Array(Array::from_values(vec![$($t.into_value()),+]))
+                }
+
+                fn from_key(key: RecordIdKey) -> Result<Self, Error> {
+                    let mut values = key_array(key, $len)?.into_iter();
+                    Ok(($(key_part::<$t>(values.next(), $i)?,)+))
+                }
+            }
+        )+
+    };
+}
+
+impl_tuple_key!(
+    1 => (A: "0"),
+    2 => (A: "0", B: "1"),
+    3 => (A: "0", B: "1", C: "2"),
+    4 => (A: "0", B: "1", C: "2", D: "3"),
+    5 => (A: "0", B: "1", C: "2", D: "3", E: "4"),
+    6 => (A: "0", B: "1", C: "2", D: "3", E: "4", F: "5"),
+);
+
+/// used by the `SurrealKey` derive
+#[doc(hidden)]
+pub fn key_array(key: RecordIdKey, len: usize) -> Result<Vec<Value>, Error> {
+    match key {
+        RecordIdKey::Array(arr) if arr.len() == len => Ok(arr.into_vec()),
+        RecordIdKey::Array(arr) => Err(Error::DeSerializeValue(format!(
+            "expected a record id key array with {len} elements, got {}",
+            arr.len()
+        ))),
+        key => Err(Error::DeSerializeValue(format!(
+            "expected an array record id key, got {key:?}"
+        ))),
+    }
+}
+
+/// used by the `SurrealKey` derive
+#[doc(hidden)]
+pub fn key_object(key: RecordIdKey) -> Result<Object, Error> {
+    match key {
+        RecordIdKey::Object(obj) => Ok(obj),
+        key => Err(Error::DeSerializeValue(format!(
+            "expected an object record id key, got {key:?}"
+        ))),
+    }
+}
+
+/// used by the `SurrealKey` derive
+#[doc(hidden)]
+pub fn key_part<T: SurrealValue>(value: Option<Value>, name: &str) -> Result<T, Error> {
+    T::from_value(value.unwrap_or(Value::None))
+        .map_err(|err| Error::DeSerializeValue(format!("record id key part `{name}`: {err}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::SurrealValue;
+
+    use crate::{RecordIdType, SurrealKey, SurrealTable, test_conn};
+
+    #[derive(Clone, Debug, PartialEq, SurrealKey)]
+    struct ReadingKey(String, i64);
+
+    #[derive(Clone, Debug, PartialEq, SurrealKey)]
+    struct TenantKey {
+        tenant: String,
+        id: i64,
+    }
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = reading)]
+    struct Reading {
+        value: i64,
+    }
+
+    #[tokio::test]
+    async fn range_of_array_keys() {
+        let conn = test_conn().await;
+        for (sensor, time) in [("a", 1), ("a", 2), ("a", 3), ("b", 2)] {
+            Reading { value: time }
+                .insert(&conn, ReadingKey(sensor.into(), time).into_key())
+                .await
+                .unwrap();
+        }
+
+        let readings = RecordIdType::<Reading>::range(
+            &conn,
+            ReadingKey("a".into(), 2)..=ReadingKey("a".into(), 3),
+        )
+        .await
+        .unwrap();
+
+        let keys = readings
+            .iter()
+            .map(|reading| reading.id.key_as::<ReadingKey>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [ReadingKey("a".into(), 2), ReadingKey("a".into(), 3)]);
+        assert_eq!(
+            readings.iter().map(|r| r.data.value).collect::<Vec<_>>(),
+            [2, 3]
+        );
+    }
+
+    #[tokio::test]
+    async fn object_key_round_trip() {
+        let conn = test_conn().await;
+        let key = TenantKey {
+            tenant: "acme".into(),
+            id: 1,
+        };
+        Reading { value: 5 }
+            .insert(&conn, key.clone().into_key())
+            .await
+            .unwrap();
+
+        let reading = RecordIdType::<Reading>::from_key(key.clone())
+            .get(&conn)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reading.id.key_as::<TenantKey>().unwrap(), key);
+        assert_eq!(reading.data.value, 5);
+        assert!(reading.id.key_as::<ReadingKey>().is_err());
+    }
+}
@@ -1,26 +1,84 @@
 #![doc=include_str!( "../readme.md")]
 
+// the derives refer to `surrealdb_extras::..`, also in the tests of this crate
+#[cfg(test)]
+extern crate self as surrealdb_extras;
+
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod codegen;
+pub mod info;
+pub mod migration;
+#[cfg(feature = "inventory")]
+pub mod registry;
+pub mod schema;
 pub mod thing;
+pub mod transaction;
 
+mod bulk;
 mod define;
 mod does_imp;
 
+mod key;
+mod live;
+mod page;
 mod query;
 mod records;
+mod select;
+mod setup;
 mod surreal_table;
 
 use serde::de::DeserializeOwned;
 
 pub use surrealdb_extras_proc_macro::*;
 
+#[doc(hidden)]
+#[cfg(feature = "inventory")]
+pub use inventory;
+
+#[doc(hidden)]
+pub use bulk::BulkOutput;
+pub use bulk::BulkQuery;
 pub use define::SurrealExt;
+pub use key::SurrealKey;
+#[doc(hidden)]
+pub use key::{key_array, key_object, key_part};
+pub use live::{LiveEvent, LiveStream};
+pub use page::{Page, PageRequest};
 pub use query::SurrealQuery;
+#[doc(hidden)]
+pub use query::{QueryBatch, take_statement};
 pub use records::{Record, RecordData};
-pub use surreal_table::SurrealTableInfo;
+pub use select::{ContainsValue, Field, Filter, FilterValue, SelectQuery};
+pub use setup::{DefineStrategy, SetupOptions};
+pub use surreal_table::{Register, SurrealTableInfo};
 pub use thing::{RecordIdFunc, RecordIdType};
+pub use transaction::Transaction;
+
+#[doc(hidden)]
+#[macro_export]
+#[cfg(not(feature = "inventory"))]
+macro_rules! __submit_table {
+    ($ty:ty) => {};
+}
 
 /// SELECT {keys} IN db
 pub trait SurrealSelectInfo: DeserializeOwned {
     /// all attributes
     fn keys() -> &'static [&'static str];
 }
+
+/// in-memory database with the namespace and database `test`
+#[cfg(test)]
+pub(crate) async fn test_conn() -> surrealdb::Surreal<surrealdb::engine::local::Db> {
+    use surrealdb::{Surreal, engine::local::Mem};
+
+    let conn = Surreal::new::<Mem>(()).await.unwrap();
+    conn.query("DEFINE NAMESPACE test; USE NS test; DEFINE DATABASE test;")
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+    conn.use_ns("test").use_db("test").await.unwrap();
+    conn
+}
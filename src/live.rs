@@ -0,0 +1,320 @@
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use futures::{Stream, StreamExt, stream::BoxStream};
+use futures_timer::Delay;
+use surrealdb::{
+    Connection, Notification, Surreal,
+    method::QueryStream,
+    types::{Action, SurrealValue, Uuid, Value, Variables},
+};
+
+use crate::{Filter, RecordData, RecordIdType, SurrealTableInfo};
+
+/// change of a table item from a LIVE query
+#[derive(Debug)]
+pub enum LiveEvent<T: SurrealTableInfo> {
+    Created(RecordData<T>),
+    Updated(RecordData<T>),
+    Deleted(RecordIdType<T>),
+}
+
+impl<T: SurrealTableInfo> LiveEvent<T> {
+    fn from_notification(notification: Notification<Value>) -> surrealdb::Result<Option<Self>> {
+        let Notification { action, data, .. } = notification;
+
+        Ok(Some(match action {
+            Action::Create => Self::Created(RecordData::from_value(data)?),
+            Action::Update => Self::Updated(RecordData::from_value(data)?),
+            Action::Delete => {
+                let id = match data {
+                    Value::Object(mut obj) => obj.remove("id").unwrap_or(Value::None),
+                    data => data,
+                };
+                Self::Deleted(RecordIdType::from_value(id)?)
+            }
+            Action::Killed => return Ok(None),
+        }))
+    }
+}
+
+/// stream of [LiveEvent]s, re-subscribes when the live query ends on a lost connection
+/// (e.g. before a reconnect) and kills the live query when dropped
+///
+/// re-subscribes wait 100ms, doubled per attempt, a failed one is yielded as an error
+/// and polling again retries, the stream ends after 5 attempts without an event
+/// or when the live query is killed
+#[must_use = "streams do nothing unless you poll them"]
+pub struct LiveStream<T: SurrealTableInfo> {
+    inner: BoxStream<'static, surrealdb::Result<LiveEvent<T>>>,
+    /// id of the current live query, known from its first notification
+    live_id: Arc<Mutex<Option<Uuid>>>,
+    kill: Box<dyn Fn(Uuid) + Send>,
+}
+
+/// delay before the first re-subscribe
+const BACKOFF: Duration = Duration::from_millis(100);
+/// re-subscribes in a row before the [LiveStream] ends
+const MAX_RETRIES: u32 = 5;
+
+struct LiveState<C: Connection> {
+    conn: Surreal<C>,
+    query: String,
+    vars: Variables,
+    stream: Option<QueryStream<Notification<Value>>>,
+    live_id: Arc<Mutex<Option<Uuid>>>,
+    /// re-subscribes since the last event
+    retries: u32,
+}
+
+impl<C: Connection> LiveState<C> {
+    async fn subscribe(&self) -> surrealdb::Result<QueryStream<Notification<Value>>> {
+        self.conn
+            .query(&self.query)
+            .bind(self.vars.clone())
+            .await?
+            .stream(0)
+    }
+
+    fn set_live_id(&self, id: Option<Uuid>) {
+        *self.live_id.lock().unwrap_or_else(PoisonError::into_inner) = id;
+    }
+
+    async fn resubscribe(&mut self) -> surrealdb::Result<()> {
+        Delay::new(BACKOFF * 2u32.pow(self.retries)).await;
+        self.retries += 1;
+
+        self.stream = Some(self.subscribe().await?);
+        Ok(())
+    }
+}
+
+impl<T: SurrealTableInfo + Send> LiveStream<T> {
+    pub(crate) async fn new<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Option<Filter<T>>,
+    ) -> surrealdb::Result<Self> {
+        let mut vars = Variables::new();
+        let query = format!(
+            "LIVE SELECT * FROM {}{};",
+            T::name(),
+            match filter {
+                Some(filter) => format!(" WHERE {}", filter.build(&mut vars)),
+                None => "".to_string(),
+            }
+        );
+
+        let live_id = Arc::new(Mutex::new(None));
+        let mut state = LiveState {
+            conn: conn.clone(),
+            query,
+            vars,
+            stream: None,
+            live_id: live_id.clone(),
+            retries: 0,
+        };
+        state.stream = Some(state.subscribe().await?);
+
+        let inner = futures::stream::unfold(Some(state), |state| async move {
+            let mut state = state?;
+
+            loop {
+                let stream = match &mut state.stream {
+                    Some(stream) => stream,
+                    None => match state.resubscribe().await {
+                        Ok(()) => continue,
+                        Err(err) if state.retries < MAX_RETRIES => {
+                            return Some((Err(err), Some(state)));
+                        }
+                        Err(err) => return Some((Err(err), None)),
+                    },
+                };
+
+                match stream.next().await {
+                    Some(Ok(notification)) => {
+                        state.set_live_id(Some(notification.query_id));
+
+                        match LiveEvent::from_notification(notification) {
+                            Ok(Some(event)) => {
+                                state.retries = 0;
+                                return Some((Ok(event), Some(state)));
+                            }
+                            // killed, re-subscribing would be killed again
+                            Ok(None) => return None,
+                            Err(err) => return Some((Err(err), Some(state))),
+                        }
+                    }
+                    Some(Err(err)) => return Some((Err(err), Some(state))),
+                    None => {
+                        state.set_live_id(None);
+
+                        // the SDK ends the stream without the `Killed` notification,
+                        // so it was killed if the connection still answers
+                        if state.conn.health().await.is_ok() {
+                            return None;
+                        }
+                        if state.retries >= MAX_RETRIES {
+                            let err = surrealdb::Error::InternalError(format!(
+                                "live query closed {MAX_RETRIES} times without an event"
+                            ));
+                            return Some((Err(err), None));
+                        }
+                        state.stream = None;
+                    }
+                }
+            }
+        });
+
+        let conn = conn.clone();
+        let kill = Box::new(move |id: Uuid| {
+            let conn = conn.clone();
+            if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                runtime.spawn(async move {
+                    conn.query("KILL $id").bind(("id", id)).await.ok();
+                });
+            }
+        });
+
+        Ok(Self {
+            inner: inner.boxed(),
+            live_id,
+            kill,
+        })
+    }
+}
+
+impl<T: SurrealTableInfo> Stream for LiveStream<T> {
+    type Item = surrealdb::Result<LiveEvent<T>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.poll_next_unpin(cx)
+    }
+}
+
+impl<T: SurrealTableInfo> Drop for LiveStream<T> {
+    // the SDK kills the live query of a dropped stream with a `KILL` local engines can't parse
+    fn drop(&mut self) {
+        let id = self
+            .live_id
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+
+        if let Some(id) = id {
+            (self.kill)(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use futures::StreamExt;
+    use surrealdb::{
+        Surreal,
+        engine::local::Db,
+        types::{Object, SurrealValue, Uuid},
+    };
+
+    use super::LiveEvent;
+    use crate::{SurrealTable, SurrealTableInfo, filter, test_conn};
+
+    #[derive(Clone, Debug, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = sensor)]
+    struct Sensor {
+        value: i64,
+    }
+
+    async fn conn() -> Surreal<Db> {
+        let conn = test_conn().await;
+        conn.query("DEFINE TABLE sensor SCHEMALESS")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        conn
+    }
+
+    async fn live_ids(conn: &Surreal<Db>) -> Vec<Uuid> {
+        let info: Option<Object> = conn
+            .query("(INFO FOR TABLE sensor).lives")
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+        info.unwrap().keys().map(|id| id.parse().unwrap()).collect()
+    }
+
+    async fn query(conn: &Surreal<Db>, sql: &str) {
+        conn.query(sql).await.unwrap().check().unwrap();
+    }
+
+    #[tokio::test]
+    async fn events_of_matching_items() {
+        let conn = conn().await;
+        let mut stream = Sensor::live(&conn, Some(filter!(Sensor, value >= 0)))
+            .await
+            .unwrap();
+
+        query(&conn, "CREATE sensor:hidden SET value = -1").await;
+        query(&conn, "CREATE sensor:a SET value = 1").await;
+        query(&conn, "UPDATE sensor:a SET value = 2").await;
+        query(&conn, "DELETE sensor:a").await;
+
+        match stream.next().await.unwrap().unwrap() {
+            LiveEvent::Created(item) => {
+                assert_eq!(item.id.to_string(), "sensor:a");
+                assert_eq!(item.data.value, 1);
+            }
+            event => panic!("{event:?}"),
+        }
+        match stream.next().await.unwrap().unwrap() {
+            LiveEvent::Updated(item) => assert_eq!(item.data.value, 2),
+            event => panic!("{event:?}"),
+        }
+        match stream.next().await.unwrap().unwrap() {
+            LiveEvent::Deleted(id) => assert_eq!(id.to_string(), "sensor:a"),
+            event => panic!("{event:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn kill_ends_the_stream() {
+        let conn = conn().await;
+        let mut stream = Sensor::live(&conn, None).await.unwrap();
+
+        let ids = live_ids(&conn).await;
+        assert_eq!(ids.len(), 1);
+        conn.query("KILL $id")
+            .bind(("id", ids[0]))
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+
+        let next = tokio::time::timeout(Duration::from_secs(1), stream.next()).await;
+        assert!(matches!(next, Ok(None)));
+    }
+
+    #[tokio::test]
+    async fn drop_kills_the_live_query() {
+        let conn = conn().await;
+        let mut stream = Sensor::live(&conn, None).await.unwrap();
+        query(&conn, "CREATE sensor:a SET value = 1").await;
+        stream.next().await.unwrap().unwrap();
+
+        drop(stream);
+        for _ in 0..100 {
+            if live_ids(&conn).await.is_empty() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("live query wasn't killed");
+    }
+}
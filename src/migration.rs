@@ -0,0 +1,597 @@
+//! versioned migrations, applied ones are recorded with their checksum in `_migrations`
+//! ```ignore
+//! let migrator = Migrator::new(embed_migrations!("migrations"))?;
+//! // or at runtime
+//! let migrator = Migrator::from_dir("migrations")?;
+//!
+//! migrator.migrate(&conn).await?;
+//! migrator.rollback(&conn).await?;
+//! ```
+//! files are named `{version}_{name}.up.surql` and optionally `{version}_{name}.down.surql`,
+//! every migration runs in its own transaction so scripts must not contain `BEGIN`/`COMMIT`
+
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use sha2::{Digest, Sha256};
+use surrealdb::{
+    Connection, Surreal,
+    types::{Datetime, SurrealValue},
+};
+
+use crate::query::{first_error, trim_query_end};
+
+/// table of applied migrations
+pub const MIGRATIONS_TABLE: &str = "_migrations";
+/// table of the record held while migrating
+const LOCK_TABLE: &str = "_migrations_lock";
+const LOCK: &str = "_migrations_lock:lock";
+
+/// both tables, a `STRICT` database rejects records of undefined ones
+fn define_tables() -> String {
+    format!(
+        "DEFINE TABLE IF NOT EXISTS {MIGRATIONS_TABLE} SCHEMALESS;
+        DEFINE TABLE IF NOT EXISTS {LOCK_TABLE} SCHEMALESS;"
+    )
+}
+
+/// one migration, see [embed_migrations](crate::embed_migrations) and [Migrator::from_dir]
+#[derive(Clone, Debug)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    pub up: String,
+    pub down: Option<String>,
+}
+
+impl Migration {
+    pub fn new(version: u64, name: impl Into<String>, up: impl Into<String>) -> Self {
+        Self {
+            version,
+            name: name.into(),
+            up: up.into(),
+            down: None,
+        }
+    }
+
+    /// script reverting `up`, needed for [Migrator::rollback]
+    pub fn with_down(mut self, down: impl Into<String>) -> Self {
+        self.down = Some(down.into());
+        self
+    }
+
+    /// hex sha256 of `up`
+    pub fn checksum(&self) -> String {
+        Sha256::digest(self.up.as_bytes())
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
+    }
+}
+
+/// row of `_migrations`
+#[derive(Clone, Debug, SurrealValue)]
+pub struct AppliedMigration {
+    pub version: u64,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: Datetime,
+}
+
+#[derive(Debug)]
+pub enum MigrationError {
+    Db(surrealdb::Error),
+    Io(PathBuf, std::io::Error),
+    /// file in the migrations directory that isn't `{version}_{name}.{up|down}.surql`
+    InvalidFile(PathBuf),
+    DuplicateVersion(u64),
+    /// no migration has this version
+    UnknownVersion(u64),
+    /// `up` of an applied migration changed
+    ChecksumMismatch {
+        version: u64,
+        applied: String,
+        current: String,
+    },
+    /// migration can't be reverted
+    MissingDown(u64),
+    /// another instance is migrating
+    Locked(String),
+    /// script failed, its transaction was cancelled
+    Failed {
+        version: u64,
+        err: surrealdb::Error,
+    },
+}
+
+impl Display for MigrationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Db(err) => write!(f, "{err}"),
+            Self::Io(path, err) => write!(f, "failed to read {}: {err}", path.display()),
+            Self::InvalidFile(path) => write!(
+                f,
+                "{} is not named {{version}}_{{name}}.up.surql or {{version}}_{{name}}.down.surql",
+                path.display()
+            ),
+            Self::DuplicateVersion(version) => write!(f, "migration {version} exists twice"),
+            Self::UnknownVersion(version) => write!(f, "migration {version} does not exist"),
+            Self::ChecksumMismatch {
+                version,
+                applied,
+                current,
+            } => write!(
+                f,
+                "migration {version} changed after it was applied (checksum {applied}, now {current})"
+            ),
+            Self::MissingDown(version) => write!(f, "migration {version} has no down script"),
+            Self::Locked(err) => write!(f, "migrations are locked by another instance: {err}"),
+            Self::Failed { version, err } => write!(f, "migration {version} failed: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Db(err) | Self::Failed { err, .. } => Some(err),
+            Self::Io(_, err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<surrealdb::Error> for MigrationError {
+    fn from(value: surrealdb::Error) -> Self {
+        Self::Db(value)
+    }
+}
+
+/// ordered set of migrations
+#[derive(Clone, Debug)]
+pub struct Migrator {
+    migrations: BTreeMap<u64, Migration>,
+    lock_timeout: Duration,
+}
+
+impl Migrator {
+    /// fails on duplicate versions
+    pub fn new(migrations: impl IntoIterator<Item = Migration>) -> Result<Self, MigrationError> {
+        let mut res = BTreeMap::new();
+
+        for migration in migrations {
+            let version = migration.version;
+            if res.insert(version, migration).is_some() {
+                return Err(MigrationError::DuplicateVersion(version));
+            }
+        }
+
+        Ok(Self {
+            migrations: res,
+            lock_timeout: Duration::from_secs(600),
+        })
+    }
+
+    /// reads `{version}_{name}.up.surql` and `{version}_{name}.down.surql` files,
+    /// other files are ignored
+    pub fn from_dir(dir: impl AsRef<Path>) -> Result<Self, MigrationError> {
+        let dir = dir.as_ref();
+        let read_err = |path: &Path| {
+            let path = path.to_path_buf();
+            move |err| MigrationError::Io(path, err)
+        };
+
+        let mut migrations = BTreeMap::<u64, (String, Option<String>, Option<String>)>::new();
+
+        for entry in std::fs::read_dir(dir).map_err(read_err(dir))? {
+            let path = entry.map_err(read_err(dir))?.path();
+            let Some(file) = path.file_name().and_then(|file| file.to_str()) else {
+                continue;
+            };
+            let Some((version, name, up)) = parse_file_name(file) else {
+                if file.ends_with(".surql") {
+                    return Err(MigrationError::InvalidFile(path));
+                }
+                continue;
+            };
+
+            let script = std::fs::read_to_string(&path).map_err(read_err(&path))?;
+            let (_, up_script, down_script) = migrations
+                .entry(version)
+                .or_insert_with(|| (name.to_string(), None, None));
+            let slot = if up { up_script } else { down_script };
+
+            if slot.replace(script).is_some() {
+                return Err(MigrationError::DuplicateVersion(version));
+            }
+        }
+
+        let migrations = migrations
+            .into_iter()
+            .map(|(version, (name, up, down))| {
+                let up = up.ok_or_else(|| {
+                    let path = dir.join(format!("{version}_{name}.up.surql"));
+                    MigrationError::Io(path, std::io::ErrorKind::NotFound.into())
+                })?;
+                let migration = Migration::new(version, name, up);
+
+                Ok::<_, MigrationError>(match down {
+                    Some(down) => migration.with_down(down),
+                    None => migration,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(migrations)
+    }
+
+    /// how long the lock of a crashed instance blocks others, 10 minutes by default
+    pub fn with_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.lock_timeout = timeout;
+        self
+    }
+
+    pub fn migrations(&self) -> impl Iterator<Item = &Migration> {
+        self.migrations.values()
+    }
+
+    /// applied migrations ordered by version
+    pub async fn applied<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+    ) -> Result<Vec<AppliedMigration>, MigrationError> {
+        let mut res = conn
+            .query(format!(
+                "{}
+                SELECT version, name, checksum, applied_at FROM {MIGRATIONS_TABLE} ORDER BY version;",
+                define_tables()
+            ))
+            .await?;
+
+        Ok(res.take(2)?)
+    }
+
+    /// applies every pending migration
+    pub async fn migrate<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+    ) -> Result<Vec<u64>, MigrationError> {
+        let latest = self.migrations.keys().last().copied().unwrap_or_default();
+        self.migrate_to(conn, latest).await
+    }
+
+    /// applies pending migrations up to `version` or reverts the ones after it,
+    /// `0` reverts everything; returns the versions that were applied or reverted in order
+    pub async fn migrate_to<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+        version: u64,
+    ) -> Result<Vec<u64>, MigrationError> {
+        if version != 0 && !self.migrations.contains_key(&version) {
+            return Err(MigrationError::UnknownVersion(version));
+        }
+
+        self.locked(conn, async || {
+            let applied = self.applied(conn).await?;
+            self.check(&applied)?;
+
+            let current = applied.last().map(|m| m.version).unwrap_or_default();
+            let mut done = vec![];
+
+            if version >= current {
+                for migration in self.migrations.range(..=version).map(|(_, m)| m) {
+                    if applied.iter().all(|m| m.version != migration.version) {
+                        self.up(conn, migration).await?;
+                        done.push(migration.version);
+                    }
+                }
+            } else {
+                for applied in applied.iter().rev().take_while(|m| m.version > version) {
+                    self.down(conn, applied.version).await?;
+                    done.push(applied.version);
+                }
+            }
+
+            Ok(done)
+        })
+        .await
+    }
+
+    /// reverts the last applied migration, returns its version
+    pub async fn rollback<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+    ) -> Result<Option<u64>, MigrationError> {
+        self.locked(conn, async || {
+            let applied = self.applied(conn).await?;
+            self.check(&applied)?;
+
+            match applied.last() {
+                Some(last) => {
+                    self.down(conn, last.version).await?;
+                    Ok(Some(last.version))
+                }
+                None => Ok(None),
+            }
+        })
+        .await
+    }
+
+    /// applied migrations must not have changed
+    fn check(&self, applied: &[AppliedMigration]) -> Result<(), MigrationError> {
+        for applied in applied {
+            if let Some(migration) = self.migrations.get(&applied.version) {
+                let current = migration.checksum();
+                if current != applied.checksum {
+                    return Err(MigrationError::ChecksumMismatch {
+                        version: applied.version,
+                        applied: applied.checksum.clone(),
+                        current,
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn up<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+        migration: &Migration,
+    ) -> Result<(), MigrationError> {
+        let record = format!(
+            "CREATE type::record('{MIGRATIONS_TABLE}', $version) \
+             SET version = $version, name = $name, checksum = $checksum, applied_at = time::now()"
+        );
+
+        run(conn, migration, &migration.up, &record).await
+    }
+
+    async fn down<C: Connection>(
+        &self,
+        conn: &Surreal<C>,
+        version: u64,
+    ) -> Result<(), MigrationError> {
+        let migration = self
+            .migrations
+            .get(&version)
+            .ok_or(MigrationError::UnknownVersion(version))?;
+        let down = migration
+            .down
+            .as_ref()
+            .ok_or(MigrationError::MissingDown(version))?;
+        let record = format!("DELETE type::record('{MIGRATIONS_TABLE}', $version)");
+
+        run(conn, migration, down, &record).await
+    }
+
+    /// runs `f` while holding the lock, a lock older than `lock_timeout` is taken over
+    async fn locked<C: Connection, R>(
+        &self,
+        conn: &Surreal<C>,
+        f: impl AsyncFnOnce() -> Result<R, MigrationError>,
+    ) -> Result<R, MigrationError> {
+        /// index of the `CREATE` statement
+        const CREATE: usize = 3;
+
+        let mut res = conn
+            .query(format!(
+                "{}
+                DELETE {LOCK} WHERE expires < time::now();
+                CREATE {LOCK} SET owner = <string> rand::uuid(), expires = time::now() + $timeout
+                    RETURN VALUE owner;",
+                define_tables()
+            ))
+            .bind(("timeout", self.lock_timeout))
+            .await?;
+
+        if let Some((ind, err)) = first_error(&mut res) {
+            // only an existing lock record, not e.g. missing permissions,
+            // the error has no kind of its own so the record is checked
+            let locked = ind == CREATE
+                && conn
+                    .query(format!("RETURN record::exists({LOCK});"))
+                    .await?
+                    .take::<Option<bool>>(0)?
+                    .unwrap_or_default();
+
+            return Err(match locked {
+                true => MigrationError::Locked(err.to_string()),
+                false => err.into(),
+            });
+        }
+        let owner: Option<String> = res.take(CREATE)?;
+
+        let res = f().await;
+        let released = conn
+            .query(format!("DELETE {LOCK} WHERE owner = $owner;"))
+            .bind(("owner", owner))
+            .await;
+
+        let res = res?;
+        released?;
+        Ok(res)
+    }
+}
+
+/// `script` and the `_migrations` change in one transaction
+async fn run<C: Connection>(
+    conn: &Surreal<C>,
+    migration: &Migration,
+    script: &str,
+    record: &str,
+) -> Result<(), MigrationError> {
+    let version = migration.version;
+    let query = format!(
+        "BEGIN TRANSACTION;\n{};\n{record};\nCOMMIT TRANSACTION;",
+        trim_query_end(script)
+    );
+
+    let mut res = conn
+        .query(query)
+        .bind(("version", version))
+        .bind(("name", migration.name.clone()))
+        .bind(("checksum", migration.checksum()))
+        .await?;
+
+    match first_error(&mut res) {
+        Some((_, err)) => Err(MigrationError::Failed { version, err }),
+        None => Ok(()),
+    }
+}
+
+/// `{version}_{name}.up.surql` -> `(version, name, true)`
+fn parse_file_name(file: &str) -> Option<(u64, &str, bool)> {
+    let (stem, up) = match file.strip_suffix(".up.surql") {
+        Some(stem) => (stem, true),
+        None => (file.strip_suffix(".down.surql")?, false),
+    };
+    let (version, name) = stem.split_once('_')?;
+
+    Some((version.parse().ok()?, name, up))
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::{Surreal, engine::local::Mem};
+
+    use super::*;
+
+    #[test]
+    fn file_names() {
+        assert_eq!(
+            parse_file_name("1_create_person.up.surql"),
+            Some((1, "create_person", true))
+        );
+        assert_eq!(
+            parse_file_name("20250101_add_index.down.surql"),
+            Some((20250101, "add_index", false))
+        );
+        assert_eq!(parse_file_name("1_create_person.surql"), None);
+        assert_eq!(parse_file_name("create_person.up.surql"), None);
+        assert_eq!(parse_file_name("v1_create_person.up.surql"), None);
+    }
+
+    #[test]
+    fn checksum_of_up() {
+        let migration = Migration::new(1, "init", "DEFINE TABLE person;");
+
+        assert_eq!(migration.checksum().len(), 64);
+        assert_eq!(
+            migration.checksum(),
+            migration
+                .clone()
+                .with_down("REMOVE TABLE person;")
+                .checksum()
+        );
+        assert_ne!(
+            migration.checksum(),
+            Migration::new(1, "init", "DEFINE TABLE people;").checksum()
+        );
+    }
+
+    #[test]
+    fn duplicate_versions() {
+        let migrations = [
+            Migration::new(1, "a", "DEFINE TABLE a;"),
+            Migration::new(1, "b", "DEFINE TABLE b;"),
+        ];
+
+        assert!(matches!(
+            Migrator::new(migrations),
+            Err(MigrationError::DuplicateVersion(1))
+        ));
+    }
+
+    async fn conn(strict: bool) -> Surreal<surrealdb::engine::local::Db> {
+        let conn = Surreal::new::<Mem>(()).await.unwrap();
+        conn.query(format!(
+            "DEFINE NAMESPACE test; USE NS test; DEFINE DATABASE test{};",
+            if strict { " STRICT" } else { "" }
+        ))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        conn.use_ns("test").use_db("test").await.unwrap();
+        conn
+    }
+
+    fn migrator() -> Migrator {
+        Migrator::new([
+            Migration::new(1, "person", "DEFINE TABLE person SCHEMALESS;")
+                .with_down("REMOVE TABLE person;"),
+            Migration::new(2, "company", "DEFINE TABLE company SCHEMALESS;")
+                .with_down("REMOVE TABLE company;"),
+        ])
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn migrate_and_rollback_in_strict_database() {
+        let conn = conn(true).await;
+        let migrator = migrator();
+
+        assert_eq!(migrator.migrate(&conn).await.unwrap(), [1, 2]);
+        assert_eq!(migrator.rollback(&conn).await.unwrap(), Some(2));
+        assert_eq!(
+            migrator
+                .applied(&conn)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|applied| applied.version)
+                .collect::<Vec<_>>(),
+            [1]
+        );
+    }
+
+    #[tokio::test]
+    async fn second_instance_is_locked_out() {
+        let conn = conn(false).await;
+        let migrator = migrator();
+
+        let res = migrator
+            .locked(&conn, async || migrator.migrate(&conn).await)
+            .await;
+        assert!(matches!(res, Err(MigrationError::Locked(_))), "{res:?}");
+
+        // released after the first one finished
+        assert_eq!(migrator.migrate(&conn).await.unwrap(), [1, 2]);
+    }
+
+    #[tokio::test]
+    async fn failed_lock_without_record_is_not_locked() {
+        let conn = conn(false).await;
+        conn.query(format!(
+            "DEFINE TABLE {LOCK_TABLE} SCHEMALESS;
+            DEFINE EVENT deny ON {LOCK_TABLE} WHEN $event = 'CREATE' THEN {{ THROW 'already exists' }};"
+        ))
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+
+        let res = migrator().migrate(&conn).await;
+        assert!(matches!(res, Err(MigrationError::Db(_))), "{res:?}");
+    }
+
+    #[tokio::test]
+    async fn script_ending_with_a_comment() {
+        let conn = conn(false).await;
+        let migrator = Migrator::new([Migration::new(
+            1,
+            "person",
+            "DEFINE TABLE person SCHEMALESS -- people",
+        )])
+        .unwrap();
+
+        assert_eq!(migrator.migrate(&conn).await.unwrap(), [1]);
+        assert_eq!(migrator.applied(&conn).await.unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,23 @@
+use crate::RecordIdFunc;
+
+/// which page to query, see [SurrealTableInfo::paginate](crate::SurrealTableInfo::paginate)
+#[derive(Clone, Debug, PartialEq)]
+pub enum PageRequest {
+    /// `START page * per_page LIMIT per_page`, keeps the order of the query
+    Offset { page: usize, per_page: usize },
+    /// the next `limit` items ordered by id, starting after `after`
+    Keyset {
+        after: Option<RecordIdFunc>,
+        limit: usize,
+    },
+}
+
+/// one page of a query
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// number of items matching the query over all pages
+    pub total: usize,
+    /// request for the following page, `None` on the last page
+    pub next: Option<PageRequest>,
+}
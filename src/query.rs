@@ -1,14 +1,448 @@
 #![allow(async_fn_in_trait)]
 
-use surrealdb::{Connection, Surreal};
+use surrealdb::{
+    Connection, IndexedResults, Surreal,
+    opt::QueryResult,
+    types::{SurrealValue, Variables},
+};
+use surrealdb_core::syn::{
+    lexer::Lexer,
+    token::{Keyword, TokenKind},
+};
 
-pub trait SurrealQuery {
+/// a query with its variables, will be created by proc macro for structs
+///
+/// `{field}` is bound as `$field`, `[[ ... ]]` is only included if its `Option` fields are `Some`
+/// ```
+/// use surrealdb_extras::SurrealQuery;
+///
+/// #[derive(SurrealQuery)]
+/// #[query(sql = "SELECT * FROM person WHERE true [[ AND age >= {min_age} ]]")]
+/// struct Search {
+///     min_age: Option<i64>,
+/// }
+///
+/// let (query, _) = Search { min_age: None }.into_parts();
+/// assert_eq!(query, "SELECT * FROM person WHERE true ");
+/// let (query, _) = Search { min_age: Some(18) }.into_parts();
+/// assert_eq!(query, "SELECT * FROM person WHERE true  AND age >= $min_age ");
+/// ```
+pub trait SurrealQuery: Sized {
     type Output;
     type Error: From<surrealdb::Error>;
 
+    /// query with every optional fragment included
     const QUERY_STR: &'static str;
+    /// number of statements in QUERY_STR
+    const STATEMENTS: usize;
 
-    async fn execute<C>(self, db: Surreal<C>) -> Result<Self::Output, Self::Error>
+    /// query to run and the variables to bind
+    fn into_parts(self) -> (&'static str, Variables);
+
+    /// takes the output from the statements starting at `offset`
+    fn take_output(res: &mut IndexedResults, offset: usize) -> surrealdb::Result<Self::Output>;
+
+    async fn execute<C>(self, db: &Surreal<C>) -> Result<Self::Output, Self::Error>
+    where
+        C: Connection,
+    {
+        let (query, vars) = self.into_parts();
+        let mut res = db.query(query).bind(vars).await?;
+
+        Ok(Self::take_output(&mut res, 0)?)
+    }
+
+    /// runs all queries in one request, the variables of each are renamed to `$q{index}_{var}`
+    async fn execute_many<C>(
+        queries: impl IntoIterator<Item = Self>,
+        db: &Surreal<C>,
+    ) -> Result<Vec<Self::Output>, Self::Error>
     where
-        C: Connection;
+        C: Connection,
+    {
+        let mut batch = QueryBatch::new(String::new(), 0);
+        for query in queries {
+            batch.push(query);
+        }
+        if batch.offsets.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let mut res = batch.run(db).await?;
+
+        let mut outputs = Vec::with_capacity(batch.offsets.len());
+        for offset in batch.offsets {
+            outputs.push(Self::take_output(&mut res, offset)?);
+        }
+
+        Ok(outputs)
+    }
+}
+
+/// several queries concatenated into one request
+#[doc(hidden)]
+pub struct QueryBatch {
+    query: String,
+    vars: Variables,
+    /// index of the first statement of every query
+    pub(crate) offsets: Vec<usize>,
+    /// index of the next statement
+    next: usize,
+}
+
+impl QueryBatch {
+    pub(crate) fn new(query: String, statements: usize) -> Self {
+        Self {
+            query,
+            vars: Variables::new(),
+            offsets: vec![],
+            next: statements,
+        }
+    }
+
+    pub(crate) fn push<Q: SurrealQuery>(&mut self, query: Q) {
+        let (query, vars) = query.into_parts();
+        let (query, vars) = namespace_vars(query, vars, &format!("q{}_", self.offsets.len()));
+
+        self.query.push_str(trim_query_end(&query));
+        self.query.push_str(";\n");
+        self.vars.extend(vars);
+        self.offsets.push(self.next);
+        self.next += Q::STATEMENTS;
+    }
+
+    pub(crate) fn push_str(&mut self, statement: &str) {
+        self.query.push_str(statement);
+    }
+
+    /// sends the request, fails with the first statement error
+    pub(crate) async fn run<C: Connection>(
+        &mut self,
+        db: &Surreal<C>,
+    ) -> surrealdb::Result<IndexedResults> {
+        let query = std::mem::take(&mut self.query);
+        let vars = std::mem::take(&mut self.vars);
+        let mut res = db.query(query).bind(vars).await?;
+
+        match first_error(&mut res) {
+            Some((ind, err)) => Err(surrealdb::Error::Query(
+                match self.offsets.iter().rposition(|offset| *offset <= ind) {
+                    Some(query) => format!(
+                        "query {query} statement {} failed: {err}",
+                        ind - self.offsets[query]
+                    ),
+                    None => format!("statement {ind} failed: {err}"),
+                },
+            )),
+            None => Ok(res),
+        }
+    }
+}
+
+/// [DbResultError](surrealdb::IndexedResults::take_errors) codes of statements that only failed
+/// because another statement in the same transaction did
+const QUERY_NOT_EXECUTED: i64 = -32003;
+const QUERY_CANCELLED: i64 = -32005;
+
+/// takes every error and returns the first one that wasn't caused by another statement
+pub(crate) fn first_error(res: &mut IndexedResults) -> Option<(usize, surrealdb::Error)> {
+    let mut errors = res.take_errors().into_iter().collect::<Vec<_>>();
+    errors.sort_by_key(|(ind, err)| {
+        let caused_by_other = matches!(err.code(), QUERY_NOT_EXECUTED | QUERY_CANCELLED);
+        (caused_by_other, *ind)
+    });
+
+    errors
+        .into_iter()
+        .next()
+        .map(|(ind, err)| (ind, surrealdb::Error::from(err)))
+}
+
+/// takes the result of statement `ind`, naming its index on failure
+#[doc(hidden)]
+pub fn take_statement<R>(res: &mut IndexedResults, ind: usize) -> Result<R, surrealdb::Error>
+where
+    R: SurrealValue,
+    usize: QueryResult<R>,
+{
+    res.take(ind)
+        .map_err(|err| surrealdb::Error::Query(format!("statement {ind}: {err}")))
+}
+
+/// renames every bound `$var` to `$prefix_var`, so queries can share one request,
+/// strings, comments and variables declared with `LET` are left alone
+pub(crate) fn namespace_vars(query: &str, vars: Variables, prefix: &str) -> (String, Variables) {
+    let mut refs = vec![];
+    let mut declared = vec![];
+    let mut after_let = false;
+
+    for token in Lexer::new(query.as_bytes()) {
+        let range = token.span.offset as usize..(token.span.offset + token.span.len) as usize;
+        match token.kind {
+            TokenKind::WhiteSpace => continue,
+            TokenKind::Parameter if after_let => declared.push(&query[range.start + 1..range.end]),
+            TokenKind::Parameter => refs.push(range),
+            _ => {}
+        }
+        after_let = token.kind == TokenKind::Keyword(Keyword::Let);
+    }
+
+    let mut res = String::with_capacity(query.len());
+    let mut last = 0;
+    for range in refs {
+        let name = &query[range.start + 1..range.end];
+        if declared.contains(&name) || vars.get(name).is_none() {
+            continue;
+        }
+
+        res.push_str(&query[last..range.start]);
+        res.push('$');
+        res.push_str(prefix);
+        res.push_str(name);
+        last = range.end;
+    }
+    res.push_str(&query[last..]);
+
+    let vars = vars
+        .into_iter()
+        .map(|(name, value)| (format!("{prefix}{name}"), value))
+        .collect();
+
+    (res, vars)
+}
+
+/// `query` up to its last token that isn't whitespace, a comment or `;`
+pub(crate) fn trim_query_end(query: &str) -> &str {
+    let end = Lexer::new(query.as_bytes())
+        .filter(|token| !matches!(token.kind, TokenKind::WhiteSpace | TokenKind::SemiColon))
+        .last()
+        .map_or(0, |token| (token.span.offset + token.span.len) as usize);
+
+    &query[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::{
+        Surreal,
+        engine::local::Db,
+        types::{Value, Variables},
+    };
+
+    use super::namespace_vars;
+    use crate::{SurrealQuery, include_surql, test_conn};
+
+    /// `$value` and `$double` clash between the batched queries
+    #[derive(SurrealQuery)]
+    #[query(
+        sql = "LET $double = {value} * 2; IF $double > 4 { THROW 'too big' }; RETURN [{value}, $double]",
+        output(2 = "Vec<i64>")
+    )]
+    struct Double {
+        value: i64,
+    }
+
+    #[derive(SurrealQuery)]
+    #[query(sql = "RETURN {value} -- the value", output = "Option<i64>")]
+    struct Commented {
+        value: i64,
+    }
+
+    /// the outputs are in the listed order, not the statement order
+    #[derive(SurrealQuery)]
+    #[query(
+        sql = "LET $values = [{value}, {value} * 2]; RETURN array::len($values); RETURN math::sum($values)",
+        output(2 = "Option<i64>", 1 = "Option<i64>")
+    )]
+    struct Stats {
+        value: i64,
+    }
+
+    #[derive(SurrealQuery)]
+    #[query(file = "tests/queries/older_than.surql", output = "Vec<String>")]
+    struct OlderThan {
+        min_age: i64,
+    }
+
+    #[derive(SurrealQuery)]
+    #[query(
+        sql = "SELECT VALUE name FROM {table} WHERE age >= $min AND name != {name} ORDER BY name",
+        output = "Vec<String>"
+    )]
+    struct Options {
+        #[var(table)]
+        table: String,
+        #[var(rename = min)]
+        age: i64,
+        #[var(with = lowercase)]
+        name: String,
+        #[var(skip)]
+        _note: String,
+    }
+
+    fn lowercase(name: String) -> String {
+        name.to_lowercase()
+    }
+
+    async fn conn_with_people() -> Surreal<Db> {
+        let conn = test_conn().await;
+        conn.query(
+            "CREATE person:ann SET name = 'ann', age = 30;
+            CREATE person:bob SET name = 'bob', age = 20;
+            CREATE person:cid SET name = 'cid', age = 40;",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        conn
+    }
+
+    fn vars(names: &[&str]) -> Variables {
+        let mut vars = Variables::new();
+        for name in names {
+            vars.insert(*name, Value::None);
+        }
+        vars
+    }
+
+    #[test]
+    fn renames_bound_vars() {
+        let (query, vars) = namespace_vars(
+            "SELECT * FROM person WHERE age > $age AND $age < $ages",
+            vars(&["age"]),
+            "q0_",
+        );
+
+        assert_eq!(
+            query,
+            "SELECT * FROM person WHERE age > $q0_age AND $q0_age < $ages"
+        );
+        assert!(vars.get("q0_age").is_some());
+        assert!(vars.get("age").is_none());
+    }
+
+    #[test]
+    fn skips_strings_and_comments() {
+        let (query, _) = namespace_vars(
+            "SELECT '$age', \"$age\" FROM person -- $age\n/* $age */ WHERE age = $age",
+            vars(&["age"]),
+            "q1_",
+        );
+
+        assert_eq!(
+            query,
+            "SELECT '$age', \"$age\" FROM person -- $age\n/* $age */ WHERE age = $q1_age"
+        );
+    }
+
+    #[test]
+    fn keeps_let_declared_vars() {
+        let (query, _) = namespace_vars(
+            "LET $name = 'x'; let $total = $limit; SELECT * FROM $name LIMIT $limit",
+            vars(&["name", "limit"]),
+            "q2_",
+        );
+
+        assert_eq!(
+            query,
+            "LET $name = 'x'; let $total = $q2_limit; SELECT * FROM $name LIMIT $q2_limit"
+        );
+    }
+
+    #[tokio::test]
+    async fn takes_listed_outputs() {
+        let conn = test_conn().await;
+        let (sum, len) = Stats { value: 3 }.execute(&conn).await.unwrap();
+
+        assert_eq!((sum, len), (Some(9), Some(2)));
+    }
+
+    #[tokio::test]
+    async fn names_failed_statement() {
+        let conn = test_conn().await;
+        let err = Double { value: 3 }.execute(&conn).await.unwrap_err();
+
+        assert!(err.to_string().starts_with("statement 1: "), "{err}");
+    }
+
+    #[tokio::test]
+    async fn execute_many_in_order() {
+        let conn = test_conn().await;
+        let outputs = Double::execute_many([1, 2, 0].map(|value| Double { value }), &conn)
+            .await
+            .unwrap();
+
+        assert_eq!(outputs, [vec![1, 2], vec![2, 4], vec![0, 0]]);
+        assert!(Double::execute_many([], &conn).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_many_names_failed_query() {
+        let conn = test_conn().await;
+        let err = Double::execute_many([1, 3].map(|value| Double { value }), &conn)
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("query 1 statement 1 failed"),
+            "{err}"
+        );
+    }
+
+    #[tokio::test]
+    async fn execute_many_after_comment() {
+        let conn = test_conn().await;
+        let outputs = Commented::execute_many([1, 2].map(|value| Commented { value }), &conn)
+            .await
+            .unwrap();
+
+        assert_eq!(outputs, [Some(1), Some(2)]);
+    }
+
+    #[tokio::test]
+    async fn query_from_file() {
+        let conn = conn_with_people().await;
+        let (query, _) = OlderThan { min_age: 30 }.into_parts();
+        assert!(query.contains("age >= $min_age"), "{query}");
+
+        let names = OlderThan { min_age: 30 }.execute(&conn).await.unwrap();
+        assert_eq!(names, ["ann", "cid"]);
+    }
+
+    #[tokio::test]
+    async fn included_surql() {
+        let conn = conn_with_people().await;
+        let names: Vec<String> = conn
+            .query(include_surql!("tests/queries/younger_than.surql"))
+            .bind(("max_age", 30))
+            .await
+            .unwrap()
+            .take(0)
+            .unwrap();
+
+        assert_eq!(names, ["bob"]);
+    }
+
+    #[tokio::test]
+    async fn var_options() {
+        let conn = conn_with_people().await;
+        let options = || Options {
+            table: "person".to_string(),
+            age: 25,
+            name: "CID".to_string(),
+            _note: "not bound".to_string(),
+        };
+
+        let (query, vars) = options().into_parts();
+        assert!(
+            query.starts_with("SELECT VALUE name FROM type::table($table)"),
+            "{query}"
+        );
+        let mut names = vars.into_iter().map(|(name, _)| name).collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["min", "name", "table"]);
+
+        assert_eq!(options().execute(&conn).await.unwrap(), ["ann"]);
+    }
 }
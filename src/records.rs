@@ -3,7 +3,7 @@ use surrealdb::{
     Connection, Error, Surreal,
     method::{Content, Delete, Merge, Patch, Select},
     opt::PatchOp,
-    types::{RecordId, SurrealValue},
+    types::{Kind, Object, RecordId, SurrealValue, Value},
 };
 
 use crate::{RecordIdFunc, SurrealSelectInfo};
@@ -77,7 +77,7 @@ impl Record {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, SurrealValue)]
+#[derive(Debug, Serialize, Deserialize)]
 /// Deserialize response into id and data
 pub struct RecordData<RD>
 where
@@ -88,6 +88,42 @@ where
     pub data: RD,
 }
 
+/// `data` is flattened next to `id`, like `#[serde(flatten)]`,
+/// which the `SurrealValue` derive ignores and would read from a nested `data` key instead
+impl<D> SurrealValue for RecordData<D>
+where
+    D: SurrealValue,
+{
+    fn kind_of() -> Kind {
+        Kind::Object
+    }
+
+    fn is_value(value: &Value) -> bool {
+        matches!(value, Value::Object(obj) if obj.get("id").is_some_and(RecordIdFunc::is_value))
+    }
+
+    fn into_value(self) -> Value {
+        let mut obj = match self.data.into_value() {
+            Value::Object(obj) => obj,
+            _ => Object::new(),
+        };
+        obj.insert("id", self.id);
+        Value::Object(obj)
+    }
+
+    fn from_value(value: Value) -> surrealdb_types::anyhow::Result<Self> {
+        let Value::Object(mut obj) = value else {
+            surrealdb_types::anyhow::bail!("Expected object, got {}", value.kind());
+        };
+        let id = RecordIdFunc::from_value(obj.remove("id").unwrap_or_default())?;
+
+        Ok(Self {
+            id,
+            data: D::from_value(Value::Object(obj))?,
+        })
+    }
+}
+
 impl<D> RecordData<D>
 where
     D: SurrealValue,
@@ -150,3 +186,28 @@ where
         self.id.get_part(conn).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::{RecordId, SurrealValue, Value};
+
+    use super::RecordData;
+
+    #[derive(Debug, PartialEq, SurrealValue, serde::Serialize, serde::Deserialize)]
+    struct Person {
+        name: String,
+    }
+
+    #[test]
+    fn data_is_next_to_id() {
+        let value = Value::from_t(surrealdb::types::object! {
+            id: RecordId::new("person", "alice"),
+            name: "Alice",
+        });
+        let record = RecordData::<Person>::from_value(value.clone()).unwrap();
+
+        assert_eq!(record.id.to_string(), "person:alice");
+        assert_eq!(record.data.name, "Alice");
+        assert_eq!(record.into_value(), value);
+    }
+}
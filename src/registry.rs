@@ -0,0 +1,126 @@
+//! link-time registry of every `#[derive(SurrealTable)]` and [register_sql](crate::register_sql)
+//! in the binary, see [SurrealExt::define_all](crate::SurrealExt::define_all)
+
+use crate::Register;
+
+/// statements submitted by [register_sql](crate::register_sql)
+#[derive(Clone, Copy, Debug)]
+pub struct RegisterSql {
+    /// module of the `register_sql!` call
+    pub path: &'static str,
+    pub sql: &'static str,
+}
+
+inventory::collect!(Register);
+inventory::collect!(RegisterSql);
+
+/// `path` is a module prefix of `item`, e.g. `app::models` of `app::models::user::User`
+fn in_module(item: &str, path: &str) -> bool {
+    path.is_empty()
+        || item
+            .strip_prefix(path)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+/// derived tables defined in module `path` or its submodules, every table if `path` is empty
+pub fn registered_tables(path: &str) -> impl Iterator<Item = Register> {
+    inventory::iter::<Register>
+        .into_iter()
+        .filter(move |register| in_module((register.path)(), path))
+        .copied()
+}
+
+/// [register_sql](crate::register_sql) statements of module `path` or its submodules
+pub fn registered_sql(path: &str) -> impl Iterator<Item = RegisterSql> {
+    inventory::iter::<RegisterSql>
+        .into_iter()
+        .filter(move |register| in_module(register.path, path))
+        .copied()
+}
+
+/// adds statements to the registry, for functions, params and other database definitions
+/// ```ignore
+/// register_sql!("DEFINE FUNCTION OVERWRITE fn::greet($name: string) { RETURN 'Hello ' + $name; }");
+/// ```
+/// they run on every [define_all](crate::SurrealExt::define_all), so use `OVERWRITE` or `IF NOT EXISTS`
+#[macro_export]
+macro_rules! register_sql {
+    ($sql:literal) => {
+        $crate::inventory::submit! {
+            $crate::registry::RegisterSql {
+                path: module_path!(),
+                sql: $crate::sql!($sql),
+            }
+        }
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __submit_table {
+    ($ty:ty) => {
+        $crate::inventory::submit! { $crate::Register::of::<$ty>() }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::SurrealValue;
+
+    use crate::{SurrealExt, SurrealTable, test_conn};
+
+    // a view can only be defined after its table, the view sorts and is declared first
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = adult, view = "SELECT name FROM person WHERE age >= 18")]
+    struct Adult {
+        name: String,
+    }
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = person)]
+    struct Person {
+        name: String,
+        age: i64,
+    }
+
+    crate::register_sql!(
+        "DEFINE FUNCTION OVERWRITE fn::adults() { RETURN SELECT VALUE name FROM adult; }"
+    );
+
+    #[tokio::test]
+    async fn define_all_in_dependency_order() {
+        let conn = test_conn().await;
+        conn.define_all_in("app", "main", module_path!())
+            .await
+            .unwrap();
+
+        let tables = conn.table_list().await.unwrap();
+        assert_eq!(tables.len(), 2);
+        assert!(tables.contains("adult") && tables.contains("person"));
+
+        conn.query(
+            "CREATE person SET name = 'ann', age = 30; CREATE person SET name = 'bob', age = 9",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        let adults: Vec<String> = conn.query("fn::adults()").await.unwrap().take(0).unwrap();
+        assert_eq!(adults, ["ann"]);
+    }
+
+    #[test]
+    fn registered_in_module() {
+        let mut tables = super::registered_tables(module_path!())
+            .map(|register| (register.name)())
+            .collect::<Vec<_>>();
+        tables.sort();
+        assert_eq!(tables, ["adult", "person"]);
+
+        assert_eq!(super::registered_sql(module_path!()).count(), 1);
+        assert_eq!(
+            super::registered_tables("surrealdb_extras::registry::test").count(),
+            0
+        );
+    }
+}
@@ -0,0 +1,449 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Formatter},
+};
+
+use crate::DefineStrategy;
+
+/// schema of a table, rendered to `DEFINE` statements by [statements](Self::statements)
+/// ```ignore
+/// #[derive(SurrealTable, ..)]
+/// #[table(
+///     db = person,
+///     permissions(select = "FULL", create = "WHERE $auth.admin"),
+///     index(name = person_email, fields = "email", unique),
+///     event(name = person_created, when = "$event = 'CREATE'", then = "CREATE log SET person = $after.id"),
+/// )]
+/// struct Person {
+///     #[opt(permissions(select = "WHERE id = $auth.id"))]
+///     email: String,
+///     #[opt(record = company)]
+///     employer: Option<RecordId>,
+/// }
+///
+/// #[derive(SurrealTable, ..)]
+/// #[table(db = works_at, relation(from = "person", to = "company", enforced))]
+/// struct WorksAt {}
+///
+/// #[derive(SurrealTable, ..)]
+/// #[table(db = person_count, view = "SELECT count() AS total FROM person GROUP ALL")]
+/// struct PersonCount {
+///     total: u64,
+/// }
+///
+/// let def = Person::table_def();
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableDef {
+    pub name: String,
+    /// `TYPE RELATION`
+    pub relation: Option<RelationDef>,
+    /// `AS` select of a view
+    pub view: Option<String>,
+    /// database default if `None`
+    pub permissions: Option<Permissions>,
+    pub fields: Vec<FieldDef>,
+    pub indexes: Vec<IndexDef>,
+    pub events: Vec<EventDef>,
+    /// raw statements of `#[table(sql(..))]`
+    pub statements: Vec<String>,
+}
+
+impl TableDef {
+    /// `DEFINE TABLE`, then fields, indexes, events and the raw statements
+    pub fn statements(&self) -> Vec<String> {
+        let Self {
+            name,
+            relation,
+            view,
+            permissions,
+            fields,
+            indexes,
+            events,
+            statements,
+        } = self;
+
+        let mut table = format!("DEFINE TABLE {name}");
+        if let Some(relation) = relation {
+            table.push_str(&format!(" {relation}"));
+        }
+        if let Some(view) = view {
+            table.push_str(&format!(" AS {view}"));
+        }
+        if let Some(permissions) = permissions {
+            table.push_str(&format!(" {permissions}"));
+        }
+
+        [table]
+            .into_iter()
+            .chain(fields.iter().map(|field| match relation {
+                // the relation already defines `in` and `out`
+                Some(_) if ["in", "out"].contains(&field.name.as_str()) => {
+                    DefineStrategy::Overwrite.apply(&field.statement(name))
+                }
+                _ => field.statement(name),
+            }))
+            .chain(indexes.iter().map(|index| index.statement(name)))
+            .chain(events.iter().map(|event| event.statement(name)))
+            .chain(statements.iter().cloned())
+            .collect()
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldDef> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// other tables this one needs, see [Dependencies]
+    pub fn dependencies(&self) -> Dependencies {
+        let (enforced, linked) = self
+            .relation
+            .iter()
+            .flat_map(|relation| {
+                relation
+                    .from
+                    .iter()
+                    .chain(&relation.to)
+                    .map(|table| (relation.enforced, table.clone()))
+            })
+            .partition::<Vec<_>, _>(|(enforced, _)| *enforced);
+
+        let hard = enforced
+            .into_iter()
+            .map(|(_, table)| table)
+            .chain(self.view.iter().flat_map(|view| from_tables(view)))
+            .filter(|table| *table != self.name)
+            .collect::<BTreeSet<_>>();
+
+        let events = self
+            .events
+            .iter()
+            .flat_map(|event| event.when.iter().chain([&event.then]));
+        let sql = events.chain(&self.statements).map(String::as_str);
+
+        let soft = self
+            .fields
+            .iter()
+            .map(|field| field.kind.as_str())
+            .chain(sql.clone())
+            .flat_map(record_tables)
+            .map(str::to_string)
+            .chain(linked.into_iter().map(|(_, table)| table))
+            .chain(sql.flat_map(from_tables))
+            .filter(|table| *table != self.name && !hard.contains(table))
+            .collect();
+
+        Dependencies { hard, soft }
+    }
+}
+
+/// tables a [TableDef] refers to, other than itself
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Dependencies {
+    /// have to be defined first: the `FROM` tables of the view
+    /// and `in`/`out` of an `ENFORCED` relation
+    pub hard: BTreeSet<String>,
+    /// only linked, the database doesn't need them yet: `record<..>` types,
+    /// `in`/`out` of a relation that is not enforced, events and raw statements
+    pub soft: BTreeSet<String>,
+}
+
+/// `a` and `b` of every `record<a | b>`
+fn record_tables(sql: &str) -> impl Iterator<Item = &str> {
+    sql.split("record<")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('>'))
+        .flat_map(|(tables, _)| tables.split('|'))
+        .map(str::trim)
+        .filter(|table| is_ident(table))
+}
+
+/// tables after every `FROM`, e.g. `a` and `b` of `SELECT * FROM a, ONLY b WHERE ..`
+fn from_tables(sql: &str) -> Vec<String> {
+    let sql = sql.replace(',', " , ");
+    let mut tokens = sql.split_whitespace();
+    let mut res = vec![];
+
+    while let Some(token) = tokens.next() {
+        if !token.eq_ignore_ascii_case("FROM") {
+            continue;
+        }
+
+        while let Some(mut table) = tokens.next() {
+            if table.eq_ignore_ascii_case("ONLY") {
+                table = tokens.next().unwrap_or_default();
+            }
+            let table = table.trim_end_matches([';', ')']);
+            if is_ident(table) {
+                res.push(table.to_string());
+            }
+            if tokens.next() != Some(",") {
+                break;
+            }
+        }
+    }
+
+    res
+}
+
+fn is_ident(table: &str) -> bool {
+    !table.is_empty()
+        && table
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// `TYPE RELATION IN .. OUT .. ENFORCED`, any table if `from`/`to` is empty
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RelationDef {
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+    /// both records have to exist
+    pub enforced: bool,
+}
+
+impl Display for RelationDef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Self { from, to, enforced } = self;
+
+        f.write_str("TYPE RELATION")?;
+        if !from.is_empty() {
+            write!(f, " IN {}", from.join(" | "))?;
+        }
+        if !to.is_empty() {
+            write!(f, " OUT {}", to.join(" | "))?;
+        }
+        if *enforced {
+            f.write_str(" ENFORCED")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FieldDef {
+    pub name: String,
+    /// SurrealQL type, e.g. `option<array<string>>`
+    pub kind: String,
+    /// database default if `None`, `delete` is ignored
+    pub permissions: Option<Permissions>,
+}
+
+impl FieldDef {
+    pub fn statement(&self, table: &str) -> String {
+        let Self {
+            name,
+            kind,
+            permissions,
+        } = self;
+
+        let mut res = format!("DEFINE FIELD {name} ON TABLE {table} TYPE {kind}");
+        if let Some(permissions) = permissions {
+            res.push_str(&format!(" {}", permissions.render(false)));
+        }
+
+        res
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexDef {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub unique: bool,
+}
+
+impl IndexDef {
+    pub fn statement(&self, table: &str) -> String {
+        let Self {
+            name,
+            fields,
+            unique,
+        } = self;
+
+        format!(
+            "DEFINE INDEX {name} ON TABLE {table} FIELDS {}{}",
+            fields.join(", "),
+            if *unique { " UNIQUE" } else { "" }
+        )
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventDef {
+    pub name: String,
+    /// `WHEN` condition, always if `None`
+    pub when: Option<String>,
+    /// `THEN` expression
+    pub then: String,
+}
+
+impl EventDef {
+    pub fn statement(&self, table: &str) -> String {
+        let Self { name, when, then } = self;
+
+        match when {
+            Some(when) => format!("DEFINE EVENT {name} ON TABLE {table} WHEN {when} THEN {then}"),
+            None => format!("DEFINE EVENT {name} ON TABLE {table} THEN {then}"),
+        }
+    }
+}
+
+/// `PERMISSIONS FOR select .., FOR create .., FOR update .., FOR delete ..`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Permissions {
+    pub select: Permission,
+    pub create: Permission,
+    pub update: Permission,
+    pub delete: Permission,
+}
+
+impl Permissions {
+    pub fn full() -> Self {
+        Self::all(Permission::Full)
+    }
+
+    pub fn none() -> Self {
+        Self::all(Permission::None)
+    }
+
+    fn all(permission: Permission) -> Self {
+        Self {
+            select: permission.clone(),
+            create: permission.clone(),
+            update: permission.clone(),
+            delete: permission,
+        }
+    }
+
+    fn render(&self, delete: bool) -> String {
+        let Self {
+            select,
+            create,
+            update,
+            delete: delete_permission,
+        } = self;
+
+        let mut res =
+            format!("PERMISSIONS FOR select {select}, FOR create {create}, FOR update {update}");
+        if delete {
+            res.push_str(&format!(", FOR delete {delete_permission}"));
+        }
+
+        res
+    }
+}
+
+impl Display for Permissions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(true))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Permission {
+    Full,
+    None,
+    /// `WHERE` condition
+    Where(String),
+}
+
+impl Permission {
+    /// `FULL`, `NONE` or a condition with or without `WHERE`
+    pub fn parse(permission: &str) -> Self {
+        let permission = permission.trim();
+
+        if permission.eq_ignore_ascii_case("full") {
+            Self::Full
+        } else if permission.eq_ignore_ascii_case("none") {
+            Self::None
+        } else {
+            let condition = match permission.get(..6) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("where ") => &permission[6..],
+                _ => permission,
+            };
+            Self::Where(condition.trim().to_string())
+        }
+    }
+}
+
+impl Display for Permission {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full => f.write_str("FULL"),
+            Self::None => f.write_str("NONE"),
+            Self::Where(condition) => write!(f, "WHERE {condition}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_tables_of_types() {
+        let tables = record_tables("option<array<record<a | b>>> | record<c>").collect::<Vec<_>>();
+        assert_eq!(tables, ["a", "b", "c"]);
+
+        assert_eq!(record_tables("record").count(), 0);
+        assert_eq!(record_tables("record<>").count(), 0);
+    }
+
+    #[test]
+    fn from_tables_of_selects() {
+        assert_eq!(
+            from_tables("SELECT * FROM a, ONLY b WHERE x = 1"),
+            ["a", "b"]
+        );
+        assert_eq!(from_tables("SELECT count() FROM (SELECT * FROM c);"), ["c"]);
+        assert_eq!(from_tables("select * from d GROUP ALL"), ["d"]);
+        assert!(from_tables("SELECT * FROM $param").is_empty());
+    }
+
+    #[test]
+    fn hard_and_soft_dependencies() {
+        let def = TableDef {
+            name: "person".into(),
+            relation: Some(RelationDef {
+                from: vec!["a".into()],
+                to: vec!["b".into()],
+                enforced: true,
+            }),
+            view: Some("SELECT * FROM c".into()),
+            fields: vec![FieldDef {
+                name: "employer".into(),
+                kind: "record<company | person>".into(),
+                ..Default::default()
+            }],
+            events: vec![EventDef {
+                name: "created".into(),
+                when: None,
+                then: "UPDATE $after.id SET total = (SELECT count() FROM log)".into(),
+            }],
+            statements: vec!["DEFINE FIELD owner ON TABLE person TYPE record<a>".into()],
+            ..Default::default()
+        };
+
+        let Dependencies { hard, soft } = def.dependencies();
+        assert_eq!(hard, BTreeSet::from(["a".into(), "b".into(), "c".into()]));
+        assert_eq!(soft, BTreeSet::from(["company".into(), "log".into()]));
+    }
+
+    #[test]
+    fn relation_that_is_not_enforced_is_soft() {
+        let def = TableDef {
+            name: "works_at".into(),
+            relation: Some(RelationDef {
+                from: vec!["person".into()],
+                to: vec!["company".into()],
+                enforced: false,
+            }),
+            ..Default::default()
+        };
+
+        let Dependencies { hard, soft } = def.dependencies();
+        assert!(hard.is_empty());
+        assert_eq!(soft, BTreeSet::from(["company".into(), "person".into()]));
+    }
+}
@@ -0,0 +1,399 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{Display, Formatter},
+};
+
+use surrealdb_core::syn::{lexer::Lexer, token::TokenKind};
+
+/// what a `DEFINE` statement defines
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DefineKind {
+    Table,
+    Field,
+    Index,
+    Event,
+}
+
+impl Display for DefineKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Table => "TABLE",
+            Self::Field => "FIELD",
+            Self::Index => "INDEX",
+            Self::Event => "EVENT",
+        })
+    }
+}
+
+/// normalized `DEFINE` statements of the code (`expected`) and the database (`current`)
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// only defined in code
+    Added {
+        expected: String,
+    },
+    /// only defined in the database
+    Removed {
+        current: String,
+    },
+    Changed {
+        current: String,
+        expected: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaChange {
+    pub table: String,
+    pub kind: DefineKind,
+    pub name: String,
+    pub change: Change,
+}
+
+impl SchemaChange {
+    /// `DEFINE ... OVERWRITE` or `REMOVE` statement applying the change
+    pub fn statement(&self) -> String {
+        let Self {
+            table,
+            kind,
+            name,
+            change,
+        } = self;
+
+        match change {
+            Change::Added { expected } | Change::Changed { expected, .. } => {
+                let prefix = format!("DEFINE {kind} ");
+                format!("DEFINE {kind} OVERWRITE {}", &expected[prefix.len()..])
+            }
+            Change::Removed { .. } => match kind {
+                DefineKind::Table => format!("REMOVE TABLE {name}"),
+                _ => format!("REMOVE {kind} {name} ON {table}"),
+            },
+        }
+    }
+}
+
+/// changes per table, fields, indexes and events of tables in the register that only exist
+/// in the database are `Removed`, other statements of `funcs()` (functions, analyzers, ...) are ignored
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaDiff {
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    pub(crate) fn new(expected: Vec<Definition>, current: Vec<Definition>) -> Self {
+        let key = |def: &Definition| (def.table.clone(), def.kind, def.name.clone());
+
+        let mut current = current
+            .into_iter()
+            .map(|def| (key(&def), def.sql))
+            .collect::<BTreeMap<_, _>>();
+        let expected = expected
+            .into_iter()
+            .map(|def| (key(&def), def.sql))
+            .collect::<BTreeMap<_, _>>();
+
+        let relations = expected
+            .iter()
+            .filter(|((_, kind, _), sql)| {
+                *kind == DefineKind::Table && sql.contains(" TYPE RELATION")
+            })
+            .map(|((table, _, _), _)| table.clone())
+            .collect::<Vec<_>>();
+
+        // implicit definitions the database adds for `array<T>`, objects and relations,
+        // e.g. `tags.*` or `in`
+        current.retain(|(table, kind, name), _| {
+            *kind != DefineKind::Field
+                || expected.contains_key(&(table.clone(), *kind, name.clone()))
+                || !(relations.contains(table) && ["in", "out"].contains(&name.as_str()))
+                    && !expected
+                        .keys()
+                        .any(|(expected_table, expected_kind, parent)| {
+                            expected_table == table
+                                && *expected_kind == DefineKind::Field
+                                && (name.starts_with(&format!("{parent}."))
+                                    || name.starts_with(&format!("{parent}[")))
+                        })
+        });
+
+        let mut changes = vec![];
+
+        for ((table, kind, name), expected) in expected {
+            let change = match current.remove(&(table.clone(), kind, name.clone())) {
+                None => Change::Added { expected },
+                Some(current) if current != expected => Change::Changed { current, expected },
+                Some(_) => continue,
+            };
+            changes.push(SchemaChange {
+                table,
+                kind,
+                name,
+                change,
+            });
+        }
+
+        changes.extend(
+            current
+                .into_iter()
+                .map(|((table, kind, name), current)| SchemaChange {
+                    table,
+                    kind,
+                    name,
+                    change: Change::Removed { current },
+                }),
+        );
+        changes.sort_by(|a, b| (&a.table, a.kind, &a.name).cmp(&(&b.table, b.kind, &b.name)));
+
+        Self { changes }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// statements applying the diff, removals (events, indexes, then fields) first,
+    /// then definitions (tables, fields, indexes, then events)
+    pub fn statements(&self) -> Vec<String> {
+        let (mut removed, mut defined): (Vec<_>, Vec<_>) = self
+            .changes
+            .iter()
+            .partition(|change| matches!(change.change, Change::Removed { .. }));
+
+        removed.sort_by_key(|change| std::cmp::Reverse(change.kind));
+        defined.sort_by_key(|change| change.kind);
+
+        removed
+            .into_iter()
+            .chain(defined)
+            .map(SchemaChange::statement)
+            .collect()
+    }
+}
+
+/// classified, normalized `DEFINE` statement
+pub(crate) struct Definition {
+    pub kind: DefineKind,
+    pub table: String,
+    pub name: String,
+    pub sql: String,
+}
+
+impl Definition {
+    /// every `DEFINE TABLE/FIELD/INDEX/EVENT` in `sql`, rendered like `INFO FOR` does
+    pub(crate) fn parse(sql: &str) -> surrealdb::Result<Vec<Self>> {
+        let parse = |sql: &str| {
+            surrealdb_core::syn::parse(sql)
+                .map_err(|err| surrealdb::Error::ParseError(err.to_string()))
+        };
+        parse(sql)?;
+
+        let mut res = vec![];
+        for statement in statements(sql) {
+            let statement = parse(statement)?.to_string();
+            res.extend(Self::classify(statement.trim().trim_end_matches(';')));
+        }
+
+        Ok(res)
+    }
+
+    fn classify(statement: &str) -> Option<Self> {
+        let mut tokens = statement.split_whitespace().peekable();
+        if tokens.next()? != "DEFINE" {
+            return None;
+        }
+
+        let kind = match tokens.next()? {
+            "TABLE" => DefineKind::Table,
+            "FIELD" => DefineKind::Field,
+            "INDEX" => DefineKind::Index,
+            "EVENT" => DefineKind::Event,
+            _ => return None,
+        };
+
+        // compared without the create strategy
+        let mut strategy = vec![];
+        while let Some(token) =
+            tokens.next_if(|token| ["OVERWRITE", "IF", "NOT", "EXISTS"].contains(token))
+        {
+            strategy.push(token);
+        }
+        let name = tokens.next()?.to_string();
+
+        let table = match kind {
+            DefineKind::Table => name.clone(),
+            _ => {
+                tokens.next_if_eq(&"ON")?;
+                tokens.next_if_eq(&"TABLE");
+                tokens.next()?.to_string()
+            }
+        };
+
+        let sql = match strategy.is_empty() {
+            true => statement.to_string(),
+            false => statement.replacen(&format!(" {}", strategy.join(" ")), "", 1),
+        };
+
+        Some(Self {
+            kind,
+            table,
+            name,
+            sql,
+        })
+    }
+}
+
+/// top level statements of `sql`, split on the `;` outside of blocks, strings and comments
+pub(crate) fn statements(sql: &str) -> Vec<&str> {
+    let mut res = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for token in Lexer::new(sql.as_bytes()) {
+        let offset = token.span.offset as usize;
+        match token.kind {
+            TokenKind::OpenDelim(_) => depth += 1,
+            TokenKind::CloseDelim(_) => depth = depth.saturating_sub(1),
+            TokenKind::SemiColon if depth == 0 => {
+                res.push(&sql[start..offset]);
+                start = offset + token.span.len as usize;
+            }
+            _ => {}
+        }
+    }
+    res.push(&sql[start..]);
+
+    res.retain(|statement| !statement.trim().is_empty());
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(definitions: &[Definition]) -> Vec<(DefineKind, &str, &str)> {
+        definitions
+            .iter()
+            .map(|def| (def.kind, def.table.as_str(), def.name.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parse_classifies_definitions() {
+        let definitions = Definition::parse(
+            "DEFINE TABLE OVERWRITE person SCHEMAFULL;
+            DEFINE FIELD name ON TABLE person TYPE string;
+            DEFINE INDEX IF NOT EXISTS person_name ON person FIELDS name UNIQUE;
+            DEFINE FUNCTION fn::greet($name: string) { RETURN 'Hello ' + $name; };
+            SELECT * FROM person;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            keys(&definitions),
+            [
+                (DefineKind::Table, "person", "person"),
+                (DefineKind::Field, "person", "name"),
+                (DefineKind::Index, "person", "person_name"),
+            ]
+        );
+        assert!(
+            definitions
+                .iter()
+                .all(|def| !def.sql.contains("OVERWRITE") && !def.sql.contains("IF NOT EXISTS"))
+        );
+    }
+
+    #[test]
+    fn parse_keeps_multi_line_bodies() {
+        let definitions = Definition::parse(
+            "DEFINE EVENT person_created ON TABLE person WHEN $event = 'CREATE' THEN {
+                CREATE log SET person = $after.id, note = 'a; b';
+                -- not the end;
+                UPDATE stats SET people += 1;
+            };
+            DEFINE TABLE person_count AS
+                SELECT count() AS total
+                FROM person
+                GROUP ALL;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            keys(&definitions),
+            [
+                (DefineKind::Event, "person", "person_created"),
+                (DefineKind::Table, "person_count", "person_count"),
+            ]
+        );
+        assert!(definitions[0].sql.contains("UPDATE stats"));
+        assert!(definitions[1].sql.contains("GROUP ALL"));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_sql() {
+        assert!(Definition::parse("DEFINE TABLE").is_err());
+    }
+
+    #[test]
+    fn diff_of_definitions() {
+        let expected = Definition::parse(
+            "DEFINE TABLE person SCHEMAFULL;
+            DEFINE FIELD name ON person TYPE string;
+            DEFINE FIELD tags ON person TYPE array<string>;
+            DEFINE FIELD age ON person TYPE int;",
+        )
+        .unwrap();
+        let current = Definition::parse(
+            "DEFINE TABLE person SCHEMAFULL;
+            DEFINE FIELD name ON person TYPE option<string>;
+            DEFINE FIELD tags ON person TYPE array<string>;
+            DEFINE FIELD tags[*] ON person TYPE string;
+            DEFINE INDEX person_name ON person FIELDS name;",
+        )
+        .unwrap();
+
+        let diff = SchemaDiff::new(expected, current);
+        let changes = diff
+            .changes
+            .iter()
+            .map(|change| {
+                let kind = match change.change {
+                    Change::Added { .. } => "added",
+                    Change::Removed { .. } => "removed",
+                    Change::Changed { .. } => "changed",
+                };
+                (change.kind, change.name.as_str(), kind)
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            changes,
+            [
+                (DefineKind::Field, "age", "added"),
+                (DefineKind::Field, "name", "changed"),
+                (DefineKind::Index, "person_name", "removed"),
+            ]
+        );
+        assert_eq!(
+            diff.statements(),
+            [
+                "REMOVE INDEX person_name ON person".to_string(),
+                "DEFINE FIELD OVERWRITE age ON person TYPE int PERMISSIONS FULL".to_string(),
+                "DEFINE FIELD OVERWRITE name ON person TYPE string PERMISSIONS FULL".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_diff_between_equal_definitions() {
+        let sql = "DEFINE TABLE person SCHEMALESS; DEFINE FIELD name ON person TYPE string";
+        let diff = SchemaDiff::new(
+            Definition::parse(sql).unwrap(),
+            Definition::parse(sql).unwrap(),
+        );
+
+        assert!(diff.is_empty());
+        assert!(diff.statements().is_empty());
+    }
+}
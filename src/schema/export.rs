@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use crate::{DefineStrategy, Register};
+
+use super::{DependencyError, define_order};
+
+/// SurrealQL script defining `ns`, `db` and the registered tables, e.g. for `surreal import`
+/// ```ignore
+/// let script = export_schema("app", "main", vec![Person::register()?], DefineStrategy::Overwrite)?;
+/// std::fs::write("schema.surql", script)?;
+/// ```
+/// tables are sorted by name and then by [dependencies](crate::schema::TableDef::dependencies),
+/// so the script only changes with the schema
+pub fn export_schema(
+    ns: &str,
+    db: &str,
+    register: impl IntoIterator<Item = Register>,
+    strategy: DefineStrategy,
+) -> Result<String, DependencyError> {
+    let mut register = register.into_iter().collect::<Vec<_>>();
+    register.sort_by_key(|register| (register.name)());
+    register.dedup_by_key(|register| (register.name)());
+
+    let mut res = format!(
+        "-- generated by surrealdb-extras\n\n\
+        DEFINE NAMESPACE IF NOT EXISTS {ns};\n\
+        USE NS {ns};\n\
+        DEFINE DATABASE IF NOT EXISTS {db};\n\
+        USE DB {db};\n"
+    );
+
+    for register in define_order(register, &HashSet::new())? {
+        res.push_str(&format!("\n-- {}\n", (register.name)()));
+        for statement in register.funcs() {
+            res.push_str(&strategy.apply(&statement));
+            res.push_str(";\n");
+        }
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::SurrealValue;
+
+    use super::export_schema;
+    use crate::{DefineStrategy, Register, SurrealExt, SurrealTable, test_conn};
+
+    // sorted after `person` by name, but defined after it as a view of it
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = adult, view = "SELECT name FROM person WHERE age >= 18")]
+    struct Adult {
+        name: String,
+    }
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = person, index(name = "person_name", fields = "name", unique))]
+    struct Person {
+        name: String,
+        age: i64,
+    }
+
+    fn script() -> String {
+        let register = [
+            Register::of::<Person>(),
+            Register::of::<Adult>(),
+            Register::of::<Person>(),
+        ];
+        export_schema("app", "main", register, DefineStrategy::Overwrite).unwrap()
+    }
+
+    #[test]
+    fn exact_script() {
+        assert_eq!(
+            script(),
+            "-- generated by surrealdb-extras
+
+DEFINE NAMESPACE IF NOT EXISTS app;
+USE NS app;
+DEFINE DATABASE IF NOT EXISTS main;
+USE DB main;
+
+-- person
+DEFINE TABLE OVERWRITE person;
+DEFINE FIELD OVERWRITE name ON TABLE person TYPE string;
+DEFINE FIELD OVERWRITE age ON TABLE person TYPE int;
+DEFINE INDEX OVERWRITE person_name ON TABLE person FIELDS name UNIQUE;
+
+-- adult
+DEFINE TABLE OVERWRITE adult AS SELECT name FROM person WHERE age >= 18;
+DEFINE FIELD OVERWRITE name ON TABLE adult TYPE string;
+"
+        );
+    }
+
+    #[tokio::test]
+    async fn script_imports() {
+        let conn = test_conn().await;
+        conn.query(script()).await.unwrap().check().unwrap();
+
+        conn.use_ns("app").use_db("main").await.unwrap();
+        let tables = conn.table_list().await.unwrap();
+        assert!(tables.contains("person") && tables.contains("adult"));
+    }
+}
@@ -0,0 +1,18 @@
+//! structured table definitions generated by `#[derive(SurrealTable)]`, the order to define them in,
+//! their `.surql` export and drift between them and the database,
+//! see [SurrealExt::schema_diff](crate::SurrealExt::schema_diff)
+
+mod def;
+mod diff;
+mod export;
+mod order;
+
+pub use def::{
+    Dependencies, EventDef, FieldDef, IndexDef, Permission, Permissions, RelationDef, TableDef,
+};
+pub(crate) use diff::Definition;
+#[cfg(feature = "cli")]
+pub(crate) use diff::statements;
+pub use diff::{Change, DefineKind, SchemaChange, SchemaDiff};
+pub use export::export_schema;
+pub use order::{DependencyError, define_order};
@@ -0,0 +1,240 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::{Display, Formatter},
+};
+
+use crate::{Register, schema::Dependencies};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DependencyError {
+    /// tables that depend on each other, the first one is repeated at the end
+    Cycle(Vec<String>),
+    /// `dependency` is neither registered nor defined in the database
+    Missing { table: String, dependency: String },
+}
+
+impl Display for DependencyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cycle(tables) => {
+                write!(f, "tables depend on each other: {}", tables.join(" -> "))
+            }
+            Self::Missing { table, dependency } => write!(
+                f,
+                "table `{table}` depends on `{dependency}`, which is neither registered nor defined"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DependencyError {}
+
+impl From<DependencyError> for surrealdb::Error {
+    fn from(value: DependencyError) -> Self {
+        surrealdb::Error::InternalError(value.to_string())
+    }
+}
+
+/// sorts the register so every table comes after its hard [dependencies](crate::schema::Dependencies),
+/// otherwise keeps the order, `defined` tables already exist in the database.
+/// Soft dependencies only break ties, tables linking each other with `record<..>` are no cycle
+pub fn define_order(
+    register: impl IntoIterator<Item = Register>,
+    defined: &HashSet<String>,
+) -> Result<Vec<Register>, DependencyError> {
+    let mut pending = register
+        .into_iter()
+        .map(|register| {
+            let dependencies = (register.table_def)().dependencies();
+            (register, (register.name)(), dependencies)
+        })
+        .collect::<Vec<_>>();
+
+    let registered = pending
+        .iter()
+        .map(|(_, name, _)| *name)
+        .collect::<HashSet<_>>();
+    for (_, table, dependencies) in &pending {
+        if let Some(dependency) = dependencies.hard.iter().find(|dependency| {
+            !registered.contains(dependency.as_str()) && !defined.contains(*dependency)
+        }) {
+            return Err(DependencyError::Missing {
+                table: table.to_string(),
+                dependency: dependency.clone(),
+            });
+        }
+    }
+
+    let mut res = Vec::with_capacity(pending.len());
+    let mut placed = HashSet::new();
+
+    let done = |placed: &HashSet<&str>, dependencies: &BTreeSet<String>| {
+        dependencies.iter().all(|dependency| {
+            placed.contains(dependency.as_str()) || !registered.contains(dependency.as_str())
+        })
+    };
+
+    while !pending.is_empty() {
+        let mut ready = pending
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, dependencies))| done(&placed, &dependencies.hard))
+            .map(|(ind, (_, _, dependencies))| (ind, done(&placed, &dependencies.soft)))
+            .peekable();
+        let first = ready.peek().copied();
+        let ready = ready.find(|(_, soft)| *soft).or(first);
+
+        match ready {
+            Some((ind, _)) => {
+                let (register, name, _) = pending.remove(ind);
+                placed.insert(name);
+                res.push(register);
+            }
+            None => return Err(DependencyError::Cycle(cycle(&pending))),
+        }
+    }
+
+    Ok(res)
+}
+
+/// follows the first pending hard dependency until a table repeats
+fn cycle(pending: &[(Register, &'static str, Dependencies)]) -> Vec<String> {
+    let mut path: Vec<&str> = vec![pending[0].1];
+
+    loop {
+        let current = path[path.len() - 1];
+        let (_, _, dependencies) = pending
+            .iter()
+            .find(|(_, name, _)| *name == current)
+            .expect("pending table");
+        let next = dependencies
+            .hard
+            .iter()
+            .map(String::as_str)
+            .find(|dependency| pending.iter().any(|(_, name, _)| name == dependency))
+            .expect("every pending table waits on another one");
+
+        if let Some(start) = path.iter().position(|table| *table == next) {
+            path.drain(..start);
+            path.push(next);
+            return path.into_iter().map(str::to_string).collect();
+        }
+        path.push(next);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{FieldDef, RelationDef, TableDef};
+
+    macro_rules! register {
+        ($name:ident $(, $field:ident: $value:expr)*) => {
+            Register {
+                name: || stringify!($name),
+                path: || "tests",
+                table_def: || TableDef {
+                    name: stringify!($name).to_string(),
+                    $($field: $value,)*
+                    ..Default::default()
+                },
+            }
+        };
+    }
+
+    fn link(name: &str, table: &str) -> FieldDef {
+        FieldDef {
+            name: name.to_string(),
+            kind: format!("option<record<{table}>>"),
+            ..Default::default()
+        }
+    }
+
+    fn names(register: Vec<Register>) -> Vec<&'static str> {
+        register
+            .into_iter()
+            .map(|register| (register.name)())
+            .collect()
+    }
+
+    #[test]
+    fn hard_dependencies_come_first() {
+        let register = [
+            register!(person_count, view: Some("SELECT count() FROM person GROUP ALL".into())),
+            register!(works_at, relation: Some(RelationDef {
+                from: vec!["person".into()],
+                to: vec!["company".into()],
+                enforced: true,
+            })),
+            register!(person),
+            register!(company),
+        ];
+
+        let order = define_order(register, &HashSet::new()).unwrap();
+        assert_eq!(
+            names(order),
+            ["person", "person_count", "company", "works_at"]
+        );
+    }
+
+    #[test]
+    fn soft_dependencies_break_ties() {
+        let register = [
+            register!(person, fields: vec![link("employer", "company")]),
+            register!(company),
+        ];
+
+        let order = define_order(register, &HashSet::new()).unwrap();
+        assert_eq!(names(order), ["company", "person"]);
+    }
+
+    #[test]
+    fn links_between_tables_are_no_cycle() {
+        let register = [
+            register!(person, fields: vec![link("employer", "company")]),
+            register!(company, fields: vec![link("owner", "person")]),
+        ];
+
+        let order = define_order(register, &HashSet::new()).unwrap();
+        assert_eq!(names(order), ["person", "company"]);
+    }
+
+    #[test]
+    fn missing_link_target_is_allowed() {
+        let register = [register!(person, fields: vec![link("employer", "company")])];
+
+        let order = define_order(register, &HashSet::new()).unwrap();
+        assert_eq!(names(order), ["person"]);
+    }
+
+    #[test]
+    fn missing_hard_dependency() {
+        let register =
+            [register!(person_count, view: Some("SELECT count() FROM person GROUP ALL".into()))];
+
+        assert_eq!(
+            define_order(register, &HashSet::new()).unwrap_err(),
+            DependencyError::Missing {
+                table: "person_count".into(),
+                dependency: "person".into(),
+            }
+        );
+
+        let defined = HashSet::from(["person".to_string()]);
+        assert!(define_order(register, &defined).is_ok());
+    }
+
+    #[test]
+    fn hard_cycle() {
+        let register = [
+            register!(other, view: Some("SELECT * FROM first".into())),
+            register!(first, view: Some("SELECT * FROM second".into())),
+            register!(second, view: Some("SELECT * FROM first".into())),
+        ];
+
+        assert_eq!(
+            define_order(register, &HashSet::new()).unwrap_err(),
+            DependencyError::Cycle(vec!["first".into(), "second".into(), "first".into()])
+        );
+    }
+}
@@ -0,0 +1,1088 @@
+use std::{marker::PhantomData, ops::Not};
+
+use futures::{Stream, StreamExt};
+use surrealdb::{
+    Connection, Surreal,
+    types::{Number, SurrealValue, Table, Value, Variables},
+};
+
+use crate::{
+    Page, PageRequest, RecordData, RecordIdFunc, SurrealSelectInfo, SurrealTableInfo,
+    query::{namespace_vars, take_statement},
+};
+
+/// field of table `T` with the rust type `V`, generated as constants by `#[derive(SurrealTable)]`
+/// ```ignore
+/// Test::select().filter(Test::RANDOM_NUMBER.gt(5)).order_by(Test::DATA, true)
+/// ```
+pub struct Field<T, V> {
+    name: &'static str,
+    _ty: PhantomData<fn() -> (T, V)>,
+}
+
+impl<T, V> Clone for Field<T, V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, V> Copy for Field<T, V> {}
+
+impl<T, V> Field<T, V> {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            _ty: PhantomData,
+        }
+    }
+
+    /// db name
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<T, V: SurrealValue> Field<T, V> {
+    fn compare(self, op: &'static str, value: Value) -> Filter<T> {
+        Filter::new(FilterNode::Compare {
+            field: self.name,
+            op,
+            value,
+        })
+    }
+
+    /// `field = value`
+    pub fn eq(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare("=", value.into_filter_value())
+    }
+
+    /// `field != value`
+    pub fn ne(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare("!=", value.into_filter_value())
+    }
+
+    /// `field > value`
+    pub fn gt(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare(">", value.into_filter_value())
+    }
+
+    /// `field >= value`
+    pub fn gte(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare(">=", value.into_filter_value())
+    }
+
+    /// `field < value`
+    pub fn lt(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare("<", value.into_filter_value())
+    }
+
+    /// `field <= value`
+    pub fn lte(self, value: impl FilterValue<V>) -> Filter<T> {
+        self.compare("<=", value.into_filter_value())
+    }
+
+    /// `field CONTAINS value`, for arrays and strings
+    pub fn contains(self, value: impl ContainsValue<V>) -> Filter<T> {
+        self.compare("CONTAINS", value.into_contains_value())
+    }
+
+    /// `field INSIDE [values]`
+    pub fn inside(self, values: impl IntoIterator<Item = impl FilterValue<V>>) -> Filter<T> {
+        let values = values
+            .into_iter()
+            .map(FilterValue::into_filter_value)
+            .collect::<Vec<_>>();
+        self.compare("INSIDE", values.into_value())
+    }
+}
+
+/// value that can be compared with a field of type `V`,
+/// `V` itself, the inner value of `Option<V>` and `&str` for strings
+#[diagnostic::on_unimplemented(message = "`{Self}` can't be compared with a field of type `{V}`")]
+pub trait FilterValue<V> {
+    fn into_filter_value(self) -> Value;
+}
+
+impl<V: SurrealValue> FilterValue<V> for V {
+    fn into_filter_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl<V: SurrealValue> FilterValue<Option<V>> for V {
+    fn into_filter_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl FilterValue<String> for &str {
+    fn into_filter_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+impl FilterValue<Option<String>> for &str {
+    fn into_filter_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+/// value a field of type `V` can contain, an item of `Vec<E>` and a substring of `String`
+#[diagnostic::on_unimplemented(message = "a field of type `{V}` can't contain `{Self}`")]
+pub trait ContainsValue<V> {
+    fn into_contains_value(self) -> Value;
+}
+
+impl<E: SurrealValue> ContainsValue<Vec<E>> for E {
+    fn into_contains_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl<E: SurrealValue> ContainsValue<Option<Vec<E>>> for E {
+    fn into_contains_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl ContainsValue<Vec<String>> for &str {
+    fn into_contains_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+impl ContainsValue<Option<Vec<String>>> for &str {
+    fn into_contains_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+impl ContainsValue<String> for String {
+    fn into_contains_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl ContainsValue<Option<String>> for String {
+    fn into_contains_value(self) -> Value {
+        self.into_value()
+    }
+}
+
+impl ContainsValue<String> for &str {
+    fn into_contains_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+impl ContainsValue<Option<String>> for &str {
+    fn into_contains_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+/// WHERE condition on table `T`, values are bound as variables,
+/// `filter!` checks the fields and the types of the values at compile time
+/// ```compile_fail
+/// use surrealdb::types::SurrealValue;
+/// use surrealdb_extras::{SurrealTable, filter};
+///
+/// #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+/// #[table(db = test)]
+/// struct Test {
+///     random_number: i64,
+/// }
+///
+/// filter!(Test, random_numbr > 5);
+/// ```
+/// ```compile_fail
+/// # use surrealdb::types::SurrealValue;
+/// # use surrealdb_extras::{SurrealTable, filter};
+/// #
+/// # #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+/// # #[table(db = test)]
+/// # struct Test {
+/// #     random_number: i64,
+/// # }
+/// filter!(Test, random_number > "5");
+/// ```
+/// ```compile_fail
+/// # use surrealdb::types::SurrealValue;
+/// # use surrealdb_extras::{SurrealTable, filter};
+/// #
+/// # #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+/// # #[table(db = test)]
+/// # struct Test {
+/// #     random_number: i64,
+/// # }
+/// filter!(Test, random_number CONTAINS true);
+/// ```
+/// ```compile_fail
+/// # use surrealdb::types::SurrealValue;
+/// # use surrealdb_extras::{SurrealTable, filter};
+/// #
+/// # #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+/// # #[table(db = test)]
+/// # struct Test {
+/// #     tags: Vec<String>,
+/// # }
+/// filter!(Test, tags CONTAINS 5);
+/// ```
+pub struct Filter<T> {
+    node: FilterNode,
+    _ty: PhantomData<fn() -> T>,
+}
+
+#[derive(Clone)]
+enum FilterNode {
+    Compare {
+        field: &'static str,
+        op: &'static str,
+        value: Value,
+    },
+    And(Vec<FilterNode>),
+    Or(Vec<FilterNode>),
+    Not(Box<FilterNode>),
+}
+
+impl<T> Clone for Filter<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.node.clone())
+    }
+}
+
+impl<T> Filter<T> {
+    fn new(node: FilterNode) -> Self {
+        Self {
+            node,
+            _ty: PhantomData,
+        }
+    }
+
+    /// both conditions
+    pub fn and(self, other: Self) -> Self {
+        Self::new(match self.node {
+            FilterNode::And(mut nodes) => {
+                nodes.push(other.node);
+                FilterNode::And(nodes)
+            }
+            node => FilterNode::And(vec![node, other.node]),
+        })
+    }
+
+    /// any of the conditions
+    pub fn or(self, other: Self) -> Self {
+        Self::new(match self.node {
+            FilterNode::Or(mut nodes) => {
+                nodes.push(other.node);
+                FilterNode::Or(nodes)
+            }
+            node => FilterNode::Or(vec![node, other.node]),
+        })
+    }
+
+    /// renders the condition, binding values as `$p{index}`
+    pub(crate) fn build(self, vars: &mut Variables) -> String {
+        self.node.build(vars)
+    }
+}
+
+impl<T> Not for Filter<T> {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self::new(FilterNode::Not(Box::new(self.node)))
+    }
+}
+
+impl FilterNode {
+    fn build(self, vars: &mut Variables) -> String {
+        match self {
+            Self::Compare { field, op, value } => {
+                let var = format!("p{}", vars.len());
+                let res = format!("{field} {op} ${var}");
+                vars.insert(var, value);
+                res
+            }
+            Self::And(nodes) => Self::join(nodes, " AND ", vars),
+            Self::Or(nodes) => Self::join(nodes, " OR ", vars),
+            Self::Not(node) => format!("!({})", node.build(vars)),
+        }
+    }
+
+    fn join(nodes: Vec<Self>, sep: &str, vars: &mut Variables) -> String {
+        let nodes = nodes
+            .into_iter()
+            .map(|node| node.build(vars))
+            .collect::<Vec<_>>();
+        format!("({})", nodes.join(sep))
+    }
+}
+
+/// typed SELECT on table `T`, returns rows of `R` with the fields of `P`,
+/// created by [SurrealTableInfo::select]
+#[must_use = "queries do nothing unless executed"]
+pub struct SelectQuery<T, P = T, R = RecordData<P>> {
+    filter: Option<Filter<T>>,
+    order: Vec<(&'static str, bool)>,
+    group: Vec<&'static str>,
+    fetch: Vec<&'static str>,
+    limit: Option<usize>,
+    start: Option<usize>,
+    _ty: PhantomData<fn() -> (P, R)>,
+}
+
+impl<T, P, R> Clone for SelectQuery<T, P, R> {
+    fn clone(&self) -> Self {
+        Self {
+            filter: self.filter.clone(),
+            order: self.order.clone(),
+            group: self.group.clone(),
+            fetch: self.fetch.clone(),
+            limit: self.limit,
+            start: self.start,
+            _ty: PhantomData,
+        }
+    }
+}
+
+impl<T: SurrealTableInfo> SelectQuery<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            filter: None,
+            order: vec![],
+            group: vec![],
+            fetch: vec![],
+            limit: None,
+            start: None,
+            _ty: PhantomData,
+        }
+    }
+}
+
+impl<T, P, R> SelectQuery<T, P, R>
+where
+    T: SurrealTableInfo,
+    P: SurrealSelectInfo + SurrealValue,
+    R: SurrealValue,
+{
+    fn with<P2, R2>(self) -> SelectQuery<T, P2, R2> {
+        let Self {
+            filter,
+            order,
+            group,
+            fetch,
+            limit,
+            start,
+            ..
+        } = self;
+
+        SelectQuery {
+            filter,
+            order,
+            group,
+            fetch,
+            limit,
+            start,
+            _ty: PhantomData,
+        }
+    }
+
+    /// WHERE, multiple filters are combined with AND
+    pub fn filter(mut self, filter: Filter<T>) -> Self {
+        self.filter = Some(match self.filter.take() {
+            Some(prev) => prev.and(filter),
+            None => filter,
+        });
+        self
+    }
+
+    /// ORDER BY, in the order of the calls
+    pub fn order_by<V>(mut self, field: Field<T, V>, desc: bool) -> Self {
+        self.order.push((field.name, desc));
+        self
+    }
+
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn start(mut self, start: usize) -> Self {
+        self.start = Some(start);
+        self
+    }
+
+    /// FETCH a record link
+    pub fn fetch<V>(mut self, field: Field<T, V>) -> Self {
+        self.fetch.push(field.name);
+        self
+    }
+
+    /// selects the fields of `P2` instead, call before [group_by](Self::group_by)
+    pub fn project<P2: SurrealSelectInfo + SurrealValue>(self) -> SelectQuery<T, P2> {
+        self.with()
+    }
+
+    /// GROUP BY, grouped rows have no id so `P` is returned without [RecordData],
+    /// the field is selected even if `P` doesn't have it
+    /// ```
+    /// use surrealdb::types::SurrealValue;
+    /// use surrealdb_extras::{SurrealSelect, SurrealTable, SurrealTableInfo};
+    ///
+    /// #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    /// #[table(db = person)]
+    /// struct Person {
+    ///     name: String,
+    ///     city: String,
+    /// }
+    ///
+    /// #[derive(SurrealSelect, SurrealValue, serde::Deserialize)]
+    /// struct Name {
+    ///     name: String,
+    /// }
+    ///
+    /// let (query, _) = Person::select()
+    ///     .project::<Name>()
+    ///     .group_by(Person::CITY)
+    ///     .into_parts();
+    /// assert_eq!(query, "SELECT name, city FROM type::table($table) GROUP BY city");
+    /// ```
+    pub fn group_by<V>(self, field: Field<T, V>) -> SelectQuery<T, P, P> {
+        let mut query = self.with::<P, P>();
+        query.group.push(field.name);
+        query
+    }
+
+    /// `SELECT {aggregate} AS agg ... GROUP ALL` with the filter of the query
+    fn aggregate_parts(self, aggregate: &str) -> (String, Variables) {
+        let mut vars = Variables::new();
+        let mut query = format!("SELECT {aggregate} AS agg FROM type::table($table)");
+
+        if let Some(filter) = self.filter {
+            let filter = filter.build(&mut vars);
+            query.push_str(&format!(" WHERE {filter}"));
+        }
+        query.push_str(" GROUP ALL");
+        vars.insert("table", Table::from(T::name()));
+
+        (query, vars)
+    }
+
+    async fn aggregate<C: Connection, V: SurrealValue>(
+        self,
+        conn: &Surreal<C>,
+        aggregate: &str,
+    ) -> surrealdb::Result<Option<V>> {
+        let (query, vars) = self.aggregate_parts(aggregate);
+        let res: Option<Aggregated<Value>> = conn.query(query).bind(vars).await?.take(0)?;
+
+        res.and_then(|res| res.agg)
+            .map(|value| match value {
+                // aggregated `math::sum` returns whole numbers as floats
+                Value::Number(Number::Float(float)) if float.fract() == 0.0 => V::from_value(value)
+                    .or_else(|_| V::from_value(Value::Number(Number::Int(float as i64)))),
+                value => V::from_value(value),
+            })
+            .transpose()
+            .map_err(|err| surrealdb::Error::DeSerializeValue(format!("{aggregate}: {err}")))
+    }
+
+    /// `math::{function}(field)` over the items where the field is set
+    async fn aggregate_field<C: Connection, V, A: SurrealValue>(
+        self,
+        conn: &Surreal<C>,
+        function: &str,
+        field: Field<T, V>,
+    ) -> surrealdb::Result<Option<A>> {
+        let query = self.filter(Filter::new(FilterNode::Compare {
+            field: field.name,
+            op: "!=",
+            value: Value::None,
+        }));
+
+        query
+            .aggregate(conn, &format!("math::{function}({})", field.name))
+            .await
+    }
+
+    /// number of matching items
+    pub async fn count<C: Connection>(self, conn: &Surreal<C>) -> surrealdb::Result<usize> {
+        count_as_usize(self.aggregate(conn, "count()").await?)
+    }
+
+    /// any item matches, stops at the first one
+    pub async fn exists<C: Connection>(self, conn: &Surreal<C>) -> surrealdb::Result<bool> {
+        let mut vars = Variables::new();
+        let mut query = "SELECT id FROM type::table($table)".to_string();
+
+        if let Some(filter) = self.filter {
+            let filter = filter.build(&mut vars);
+            query.push_str(&format!(" WHERE {filter}"));
+        }
+        query.push_str(" LIMIT 1");
+        vars.insert("table", Table::from(T::name()));
+
+        let res: Vec<Value> = conn.query(query).bind(vars).await?.take(0)?;
+        Ok(!res.is_empty())
+    }
+
+    /// `math::sum`, `V::default()` without matching items
+    pub async fn sum<C: Connection, V: SurrealValue + Default>(
+        self,
+        conn: &Surreal<C>,
+        field: Field<T, V>,
+    ) -> surrealdb::Result<V> {
+        let sum = self.aggregate_field(conn, "sum", field).await?;
+        Ok(sum.unwrap_or_default())
+    }
+
+    /// `math::mean`, `None` without matching items
+    pub async fn mean<C: Connection, V>(
+        self,
+        conn: &Surreal<C>,
+        field: Field<T, V>,
+    ) -> surrealdb::Result<Option<f64>> {
+        self.aggregate_field(conn, "mean", field).await
+    }
+
+    /// `math::min`, `None` without matching items
+    pub async fn min<C: Connection, V: SurrealValue>(
+        self,
+        conn: &Surreal<C>,
+        field: Field<T, V>,
+    ) -> surrealdb::Result<Option<V>> {
+        self.aggregate_field(conn, "min", field).await
+    }
+
+    /// `math::max`, `None` without matching items
+    pub async fn max<C: Connection, V: SurrealValue>(
+        self,
+        conn: &Surreal<C>,
+        field: Field<T, V>,
+    ) -> surrealdb::Result<Option<V>> {
+        self.aggregate_field(conn, "max", field).await
+    }
+
+    /// query and the variables to bind
+    pub fn into_parts(self) -> (String, Variables) {
+        let mut vars = Variables::new();
+
+        let mut keys = P::keys()
+            .iter()
+            .filter(|key| self.group.is_empty() || **key != "id")
+            .copied()
+            .collect::<Vec<_>>();
+        // GROUP BY and ORDER BY only accept selected fields
+        for field in self
+            .group
+            .iter()
+            .chain(self.order.iter().map(|(field, _)| field))
+        {
+            if !keys.contains(field) {
+                keys.push(field);
+            }
+        }
+        let mut query = format!("SELECT {} FROM type::table($table)", keys.join(", "));
+
+        if let Some(filter) = self.filter {
+            let filter = filter.build(&mut vars);
+            query.push_str(&format!(" WHERE {filter}"));
+        }
+        if !self.group.is_empty() {
+            query.push_str(&format!(" GROUP BY {}", self.group.join(", ")));
+        }
+        if !self.order.is_empty() {
+            let order = self
+                .order
+                .iter()
+                .map(|(field, desc)| match desc {
+                    true => format!("{field} DESC"),
+                    false => format!("{field} ASC"),
+                })
+                .collect::<Vec<_>>();
+            query.push_str(&format!(" ORDER BY {}", order.join(", ")));
+        }
+        if let Some(limit) = self.limit {
+            query.push_str(" LIMIT $limit");
+            vars.insert("limit", limit as i64);
+        }
+        if let Some(start) = self.start {
+            query.push_str(" START $start");
+            vars.insert("start", start as i64);
+        }
+        if !self.fetch.is_empty() {
+            query.push_str(&format!(" FETCH {}", self.fetch.join(", ")));
+        }
+
+        vars.insert("table", Table::from(T::name()));
+
+        (query, vars)
+    }
+
+    pub async fn execute<C: Connection>(self, conn: &Surreal<C>) -> surrealdb::Result<Vec<R>> {
+        let (query, vars) = self.into_parts();
+        conn.query(query).bind(vars).await?.take(0)
+    }
+}
+
+/// result of `SELECT {aggregate} AS agg ... GROUP ALL`
+#[derive(SurrealValue)]
+struct Aggregated<V: SurrealValue> {
+    agg: Option<V>,
+}
+
+/// `count()` as `usize`, `0` without matching items
+fn count_as_usize(count: Option<i64>) -> surrealdb::Result<usize> {
+    usize::try_from(count.unwrap_or_default())
+        .map_err(|err| surrealdb::Error::DeSerializeValue(format!("count(): {err}")))
+}
+
+impl<T, P> SelectQuery<T, P>
+where
+    T: SurrealTableInfo,
+    P: SurrealSelectInfo + SurrealValue,
+{
+    /// the next `limit` items ordered by id after `after`
+    fn keyset(mut self, after: Option<RecordIdFunc>, limit: usize) -> Self {
+        if let Some(after) = after {
+            self = self.filter(Filter::new(FilterNode::Compare {
+                field: "id",
+                op: ">",
+                value: after.into_value(),
+            }));
+        }
+        self.order = vec![("id", false)];
+        self.start = None;
+        self.limit = Some(limit);
+        self
+    }
+
+    /// every matching item, queried in batches of `batch_size` ordered by id,
+    /// ignores `order_by`, `limit` and `start`
+    pub fn stream<C: Connection>(
+        self,
+        conn: &Surreal<C>,
+        batch_size: usize,
+    ) -> impl Stream<Item = surrealdb::Result<RecordData<P>>> + Send + 'static
+    where
+        P: Send + 'static,
+    {
+        let batch_size = batch_size.max(1);
+        let state = (conn.clone(), self, Some(None::<RecordIdFunc>));
+
+        futures::stream::unfold(state, move |(conn, query, after)| async move {
+            let after = after?;
+            let batch = query.clone().keyset(after, batch_size).execute(&conn).await;
+
+            let next = match &batch {
+                Ok(items) if items.len() == batch_size => {
+                    items.last().map(|item| Some(item.id.clone()))
+                }
+                _ => None,
+            };
+
+            Some((batch, (conn, query, next)))
+        })
+        .flat_map(|batch| {
+            futures::stream::iter(match batch {
+                Ok(items) => items.into_iter().map(Ok).collect::<Vec<_>>(),
+                Err(err) => vec![Err(err)],
+            })
+        })
+    }
+
+    /// one page of the query with the total number of matching items, in one request
+    pub async fn paginate<C: Connection>(
+        self,
+        conn: &Surreal<C>,
+        request: PageRequest,
+    ) -> surrealdb::Result<Page<RecordData<P>>> {
+        let count = self.clone();
+
+        let mut query = self;
+        match &request {
+            PageRequest::Offset { page, per_page } => {
+                query.start = Some(page * per_page);
+                query.limit = Some(*per_page);
+            }
+            PageRequest::Keyset { after, limit } => {
+                // one more to know if there is a next page
+                query = query.keyset(after.clone(), limit + 1);
+            }
+        }
+        let (items_query, mut vars) = query.into_parts();
+
+        let (count_query, count_vars) = count.aggregate_parts("count()");
+        let (count_query, count_vars) = namespace_vars(&count_query, count_vars, "count_");
+        vars.extend(count_vars);
+
+        let mut res = conn
+            .query(items_query)
+            .query(count_query)
+            .bind(vars)
+            .await?;
+        let mut items: Vec<RecordData<P>> = take_statement(&mut res, 0)?;
+        let total = count_as_usize(
+            take_statement::<Option<Aggregated<i64>>>(&mut res, 1)?.and_then(|count| count.agg),
+        )?;
+
+        let next = match request {
+            PageRequest::Offset { page, per_page } => {
+                ((page + 1) * per_page < total).then_some(PageRequest::Offset {
+                    page: page + 1,
+                    per_page,
+                })
+            }
+            PageRequest::Keyset { limit, .. } => (items.len() > limit).then(|| {
+                items.truncate(limit);
+                PageRequest::Keyset {
+                    after: items.last().map(|item| item.id.clone()),
+                    limit,
+                }
+            }),
+        };
+
+        Ok(Page { items, total, next })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+    use surrealdb::{
+        Surreal,
+        engine::local::Db,
+        types::{SurrealValue, Value, Variables},
+    };
+
+    use crate::{
+        Filter, PageRequest, RecordData, RecordIdFunc, RecordIdType, SurrealSelect, SurrealTable,
+        SurrealTableInfo, filter, test_conn,
+    };
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = person)]
+    struct Person {
+        name: String,
+        age: i64,
+        tags: Vec<String>,
+        nickname: Option<String>,
+    }
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = pet)]
+    struct Pet {
+        name: String,
+        owner: RecordIdType<Person>,
+    }
+
+    #[derive(SurrealSelect, SurrealValue, serde::Deserialize)]
+    struct Name {
+        name: String,
+    }
+
+    #[derive(SurrealSelect, SurrealValue, serde::Deserialize)]
+    struct Age {
+        age: i64,
+    }
+
+    #[derive(SurrealSelect, SurrealValue, serde::Deserialize)]
+    struct PetOwner {
+        name: String,
+        owner: RecordData<Name>,
+    }
+
+    async fn conn_with_people() -> Surreal<Db> {
+        let conn = test_conn().await;
+        conn.query(
+            "CREATE person:ann SET name = 'ann', age = 30, tags = ['rust'];
+            CREATE person:bob SET name = 'bob', age = 20, tags = [];
+            CREATE person:cid SET name = 'cid', age = 40, tags = ['rust', 'go'], nickname = 'c';
+            CREATE person:dan SET name = 'dan', age = 20, tags = ['go'];
+            CREATE pet:rex SET name = 'rex', owner = person:cid;",
+        )
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+        conn
+    }
+
+    fn names<P: SurrealValue>(items: &[RecordData<P>], name: impl Fn(&P) -> &str) -> Vec<&str> {
+        items.iter().map(|item| name(&item.data)).collect()
+    }
+
+    fn build(filter: Filter<Person>) -> (String, Variables) {
+        let mut vars = Variables::new();
+        (filter.build(&mut vars), vars)
+    }
+
+    fn var(vars: &Variables, name: &str) -> Value {
+        vars.get(name).cloned().unwrap()
+    }
+
+    #[test]
+    fn filter_binds_values() {
+        let min = 18;
+        let (cond, vars) = build(filter!(Person, age >= $min && name == "a"));
+
+        assert_eq!(cond, "(age >= $p0 AND name = $p1)");
+        assert_eq!(vars.len(), 2);
+        assert_eq!(var(&vars, "p0"), 18i64.into_value());
+        assert_eq!(var(&vars, "p1"), "a".to_string().into_value());
+    }
+
+    #[test]
+    fn filter_precedence() {
+        let (cond, vars) = build(filter!(
+            Person,
+            age < 10 || age > 60 && !(nickname == "x" OR name != "y")
+        ));
+
+        assert_eq!(
+            cond,
+            "(age < $p0 OR (age > $p1 AND !((nickname = $p2 OR name != $p3))))"
+        );
+        assert_eq!(var(&vars, "p1"), 60i64.into_value());
+        assert_eq!(var(&vars, "p2"), "x".to_string().into_value());
+    }
+
+    #[test]
+    fn filter_inside_and_contains() {
+        let (cond, vars) = build(filter!(
+            Person,
+            name INSIDE ["a", "b"] && tags CONTAINS "rust" && name CONTAINS "a" && age IN [-1, 2]
+        ));
+
+        assert_eq!(
+            cond,
+            "(name INSIDE $p0 AND tags CONTAINS $p1 AND name CONTAINS $p2 AND age INSIDE $p3)"
+        );
+        assert_eq!(
+            var(&vars, "p0"),
+            vec!["a".to_string(), "b".to_string()].into_value()
+        );
+        assert_eq!(var(&vars, "p1"), "rust".to_string().into_value());
+        assert_eq!(var(&vars, "p3"), vec![-1i64, 2].into_value());
+    }
+
+    #[test]
+    fn filter_in_select() {
+        let (query, vars) = Person::select()
+            .filter(filter!(Person, age > 5))
+            .into_parts();
+
+        assert_eq!(
+            query,
+            "SELECT id, name, age, tags, nickname FROM type::table($table) WHERE age > $p0"
+        );
+        assert_eq!(var(&vars, "p0"), 5i64.into_value());
+    }
+
+    #[tokio::test]
+    async fn select_filters_orders_and_limits() {
+        let conn = conn_with_people().await;
+        let people = Person::select()
+            .filter(filter!(Person, tags CONTAINS "rust" || age == 20))
+            .order_by(Person::AGE, true)
+            .order_by(Person::NAME, false)
+            .limit(2)
+            .start(1)
+            .execute(&conn)
+            .await
+            .unwrap();
+
+        assert_eq!(names(&people, |p| &p.name), ["ann", "bob"]);
+        assert_eq!(people[0].id.to_string(), "person:ann");
+    }
+
+    #[tokio::test]
+    async fn select_projection_and_group() {
+        let conn = conn_with_people().await;
+        let people = Person::select()
+            .project::<Name>()
+            .filter(filter!(Person, nickname == "c"))
+            .execute(&conn)
+            .await
+            .unwrap();
+        assert_eq!(names(&people, |p| &p.name), ["cid"]);
+
+        let mut ages = Person::select()
+            .project::<Age>()
+            .group_by(Person::AGE)
+            .execute(&conn)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|age| age.age)
+            .collect::<Vec<_>>();
+        ages.sort();
+        assert_eq!(ages, [20, 30, 40]);
+    }
+
+    #[tokio::test]
+    async fn select_fetch() {
+        let conn = conn_with_people().await;
+        let pets = Pet::select()
+            .project::<PetOwner>()
+            .fetch(Pet::OWNER)
+            .execute(&conn)
+            .await
+            .unwrap();
+
+        assert_eq!(names(&pets, |p| &p.name), ["rex"]);
+        assert_eq!(pets[0].data.owner.data.name, "cid");
+    }
+
+    #[tokio::test]
+    async fn paginate_by_offset() {
+        let conn = conn_with_people().await;
+        let query = Person::select().order_by(Person::NAME, true);
+
+        let page = query
+            .clone()
+            .paginate(
+                &conn,
+                PageRequest::Offset {
+                    page: 0,
+                    per_page: 3,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(names(&page.items, |p| &p.name), ["dan", "cid", "bob"]);
+        assert_eq!(page.total, 4);
+        assert_eq!(
+            page.next,
+            Some(PageRequest::Offset {
+                page: 1,
+                per_page: 3
+            })
+        );
+
+        let page = query.paginate(&conn, page.next.unwrap()).await.unwrap();
+        assert_eq!(names(&page.items, |p| &p.name), ["ann"]);
+        assert_eq!(page.total, 4);
+        assert_eq!(page.next, None);
+    }
+
+    #[tokio::test]
+    async fn paginate_by_keyset() {
+        let conn = conn_with_people().await;
+        let query = Person::select().filter(filter!(Person, name != "bob"));
+        let request = PageRequest::Keyset {
+            after: None,
+            limit: 2,
+        };
+
+        let page = query.clone().paginate(&conn, request).await.unwrap();
+        assert_eq!(names(&page.items, |p| &p.name), ["ann", "cid"]);
+        assert_eq!(page.total, 3);
+        assert_eq!(
+            page.next,
+            Some(PageRequest::Keyset {
+                after: Some(RecordIdFunc::from(("person", "cid"))),
+                limit: 2,
+            })
+        );
+
+        let page = query.paginate(&conn, page.next.unwrap()).await.unwrap();
+        assert_eq!(names(&page.items, |p| &p.name), ["dan"]);
+        assert_eq!(page.next, None);
+
+        let page = Person::paginate(
+            &conn,
+            PageRequest::Keyset {
+                after: None,
+                limit: 10,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(page.items.len(), 4);
+        assert_eq!(page.next, None);
+    }
+
+    #[tokio::test]
+    async fn stream_in_batches() {
+        let conn = conn_with_people().await;
+
+        // a last batch that is full and one that isn't
+        for batch_size in [2, 3] {
+            let people: Vec<_> = Person::stream_all(&conn, batch_size)
+                .try_collect()
+                .await
+                .unwrap();
+            assert_eq!(names(&people, |p| &p.name), ["ann", "bob", "cid", "dan"]);
+        }
+
+        // ordered by id regardless of `order_by`
+        let people: Vec<_> = Person::select()
+            .filter(filter!(Person, age == 20))
+            .order_by(Person::NAME, true)
+            .stream(&conn, 1)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(names(&people, |p| &p.name), ["bob", "dan"]);
+    }
+
+    #[tokio::test]
+    async fn count_and_exists() {
+        let conn = conn_with_people().await;
+
+        assert_eq!(Person::count(&conn, None).await.unwrap(), 4);
+        assert_eq!(
+            Person::count(&conn, Some(filter!(Person, age == 20)))
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(
+            Person::exists(&conn, Some(filter!(Person, tags CONTAINS "go")))
+                .await
+                .unwrap()
+        );
+        assert!(
+            !Person::exists(&conn, Some(filter!(Person, age > 100)))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            Pet::count(&conn, Some(filter!(Pet, name == "tom")))
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn aggregates() {
+        let conn = conn_with_people().await;
+        let query = Person::select().filter(filter!(Person, name != "bob"));
+
+        assert_eq!(query.clone().sum(&conn, Person::AGE).await.unwrap(), 90);
+        assert_eq!(
+            query.clone().mean(&conn, Person::AGE).await.unwrap(),
+            Some(30.0)
+        );
+        assert_eq!(
+            query.clone().min(&conn, Person::AGE).await.unwrap(),
+            Some(20)
+        );
+        assert_eq!(query.max(&conn, Person::AGE).await.unwrap(), Some(40));
+    }
+
+    #[tokio::test]
+    async fn aggregates_without_items() {
+        let conn = conn_with_people().await;
+        let query = Person::select().filter(filter!(Person, age > 100));
+
+        assert_eq!(query.clone().count(&conn).await.unwrap(), 0);
+        assert_eq!(query.clone().sum(&conn, Person::AGE).await.unwrap(), 0);
+        assert_eq!(query.clone().mean(&conn, Person::AGE).await.unwrap(), None);
+        assert_eq!(query.clone().min(&conn, Person::AGE).await.unwrap(), None);
+        assert_eq!(query.max(&conn, Person::AGE).await.unwrap(), None);
+    }
+}
@@ -0,0 +1,182 @@
+/// how [setup](crate::SurrealExt::setup) defines the registered tables
+/// ```ignore
+/// let planned = conn
+///     .setup("ns", "db", registers, SetupOptions {
+///         dry_run: true,
+///         strategy: DefineStrategy::Overwrite,
+///     })
+///     .await?;
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SetupOptions {
+    /// returns the statements without running them
+    pub dry_run: bool,
+    pub strategy: DefineStrategy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+pub enum DefineStrategy {
+    /// only tables missing in the database, existing ones are not touched
+    #[default]
+    Missing,
+    /// every table with `DEFINE .. OVERWRITE`
+    Overwrite,
+    /// every table with `DEFINE .. IF NOT EXISTS`, adds missing fields, indexes and events
+    IfNotExists,
+}
+
+impl DefineStrategy {
+    /// adds the strategy to a `DEFINE` statement without one, e.g. `DEFINE TABLE OVERWRITE person`
+    pub fn apply(&self, statement: &str) -> String {
+        let keyword = match self {
+            Self::Missing => return statement.to_string(),
+            Self::Overwrite => "OVERWRITE",
+            Self::IfNotExists => "IF NOT EXISTS",
+        };
+
+        let mut tokens = statement.trim_start().splitn(3, char::is_whitespace);
+        match (
+            tokens.next(),
+            tokens.next(),
+            tokens.next().map(str::trim_start),
+        ) {
+            (Some(define), Some(kind), Some(rest))
+                if define.eq_ignore_ascii_case("DEFINE")
+                    && !rest.split_whitespace().next().is_some_and(|name| {
+                        name.eq_ignore_ascii_case("OVERWRITE") || name.eq_ignore_ascii_case("IF")
+                    }) =>
+            {
+                format!("{define} {kind} {keyword} {rest}")
+            }
+            _ => statement.to_string(),
+        }
+    }
+}
+
+/// statement of [setup](crate::SurrealExt::setup) through the `tracing` or `log` feature
+#[cfg_attr(
+    not(any(feature = "tracing", feature = "log")),
+    allow(unused_variables)
+)]
+pub(crate) fn log_statement(statement: &str, dry_run: bool) {
+    #[cfg(feature = "tracing")]
+    match dry_run {
+        true => tracing::debug!(target: "surrealdb_extras::setup", "planned: {statement}"),
+        false => tracing::info!(target: "surrealdb_extras::setup", "{statement}"),
+    }
+
+    #[cfg(feature = "log")]
+    match dry_run {
+        true => log::debug!(target: "surrealdb_extras::setup", "planned: {statement}"),
+        false => log::info!(target: "surrealdb_extras::setup", "{statement}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::SurrealValue;
+
+    use super::{DefineStrategy, SetupOptions};
+    use crate::{Register, SurrealExt, SurrealTable, SurrealTableInfo, test_conn};
+
+    #[derive(Clone, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize)]
+    #[table(db = item)]
+    struct Item {
+        name: String,
+    }
+
+    fn dry_run(strategy: DefineStrategy) -> SetupOptions {
+        SetupOptions {
+            dry_run: true,
+            strategy,
+        }
+    }
+
+    #[test]
+    fn apply() {
+        let statement = "DEFINE FIELD name ON TABLE person TYPE string";
+
+        assert_eq!(DefineStrategy::Missing.apply(statement), statement);
+        assert_eq!(
+            DefineStrategy::Overwrite.apply(statement),
+            "DEFINE FIELD OVERWRITE name ON TABLE person TYPE string"
+        );
+        assert_eq!(
+            DefineStrategy::IfNotExists.apply("  define table person"),
+            "define table IF NOT EXISTS person"
+        );
+    }
+
+    #[test]
+    fn apply_keeps_existing_strategy() {
+        for statement in [
+            "DEFINE TABLE OVERWRITE person",
+            "DEFINE TABLE IF NOT EXISTS person",
+            "define table overwrite person",
+        ] {
+            assert_eq!(DefineStrategy::Overwrite.apply(statement), statement);
+            assert_eq!(DefineStrategy::IfNotExists.apply(statement), statement);
+        }
+    }
+
+    #[test]
+    fn apply_ignores_other_statements() {
+        for statement in ["REMOVE TABLE person", "DEFINE", "DEFINE TABLE"] {
+            assert_eq!(DefineStrategy::Overwrite.apply(statement), statement);
+        }
+    }
+
+    #[tokio::test]
+    async fn dry_run_without_database() {
+        let conn = test_conn().await;
+        let register = || [Register::of::<Item>()];
+
+        let planned = conn
+            .setup("app", "main", register(), dry_run(DefineStrategy::Missing))
+            .await
+            .unwrap();
+        assert_eq!(
+            planned[..2],
+            ["DEFINE NAMESPACE app", "DEFINE DATABASE main"]
+        );
+        assert!(!conn.info_kv().await.unwrap().namespaces.contains_key("app"));
+
+        let ran = conn
+            .setup("app", "main", register(), SetupOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(ran, planned);
+        assert!(conn.table_list().await.unwrap().contains("item"));
+    }
+
+    #[tokio::test]
+    async fn dry_run_keeps_tables() {
+        let conn = test_conn().await;
+        conn.query("DEFINE TABLE item SCHEMALESS")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        let tables = conn.info_db().await.unwrap().tables;
+        let fields = conn.info_table("item").await.unwrap().fields;
+
+        let planned = conn
+            .setup(
+                "test",
+                "test",
+                [Register::of::<Item>()],
+                dry_run(DefineStrategy::Overwrite),
+            )
+            .await
+            .unwrap();
+        let expected = Item::funcs()
+            .iter()
+            .map(|statement| DefineStrategy::Overwrite.apply(statement))
+            .collect::<Vec<_>>();
+        assert_eq!(planned, expected);
+
+        assert_eq!(conn.info_db().await.unwrap().tables, tables);
+        assert_eq!(conn.info_table("item").await.unwrap().fields, fields);
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(async_fn_in_trait)]
 
+use futures::Stream;
 use serde::Serialize;
 use surrealdb::{
     Connection, Surreal,
@@ -7,12 +8,36 @@ use surrealdb::{
 };
 use surrealdb_types::{SurrealValue, ToSql};
 
-use crate::{Record, RecordData, SurrealSelectInfo};
+use crate::{
+    BulkQuery, Filter, LiveStream, Page, PageRequest, Record, RecordData, SelectQuery,
+    SurrealSelectInfo, schema::TableDef,
+};
 
 type F1 = fn() -> &'static str;
-type F3 = fn() -> Vec<String>;
+type F3 = fn() -> TableDef;
+
+/// table to define, see [SurrealTableInfo::register]
+#[derive(Clone, Copy, Debug)]
+pub struct Register {
+    pub name: F1,
+    pub path: F1,
+    pub table_def: F3,
+}
 
-pub type Register = (F1, F1, F3);
+impl Register {
+    pub const fn of<T: SurrealTableInfo>() -> Self {
+        Self {
+            name: T::name,
+            path: T::path,
+            table_def: T::table_def,
+        }
+    }
+
+    /// `DEFINE` statements of the table
+    pub fn funcs(&self) -> Vec<String> {
+        (self.table_def)().statements()
+    }
+}
 
 /// usefull functions for db
 /// will be created by proc macro
@@ -23,8 +48,13 @@ pub trait SurrealTableInfo: Serialize + SurrealValue + SurrealSelectInfo + Clone
     fn path() -> &'static str;
     /// defines what attr to exclude in check_if_exists
     fn exclude() -> &'static [&'static str];
-    /// register attr
-    fn funcs() -> Vec<String>;
+    /// fields, indexes, events and permissions
+    fn table_def() -> TableDef;
+
+    /// `DEFINE` statements of [table_def](Self::table_def)
+    fn funcs() -> Vec<String> {
+        Self::table_def().statements()
+    }
 
     /// checks if item exists in table and returns the result
     async fn check_if_exists<'a, C: Connection>(
@@ -81,6 +111,7 @@ pub trait SurrealTableInfo: Serialize + SurrealValue + SurrealSelectInfo + Clone
     }
 
     /// search db
+    #[deprecated(note = "use `select()` with generated field constants")]
     async fn search<
         T: SurrealValue + SurrealSelectInfo + serde::de::DeserializeOwned,
         C: Connection,
@@ -117,21 +148,102 @@ pub trait SurrealTableInfo: Serialize + SurrealValue + SurrealSelectInfo + Clone
         Ok(r.is_some())
     }
 
-    /// returns every item in table
+    /// returns every item in table, see [stream_all](Self::stream_all) for large tables
     fn all<T: serde::Serialize, C: Connection>(
         conn: &'_ Surreal<C>,
     ) -> surrealdb::method::Select<'_, C, Vec<T>> {
         conn.select(Self::name())
     }
 
-    /// returns functions for register
+    /// typed SELECT on the table
+    /// ```ignore
+    /// let items: Vec<RecordData<Test>> = Test::select()
+    ///     .filter(Test::RANDOM_NUMBER.gt(5))
+    ///     .order_by(Test::RANDOM_NUMBER, true)
+    ///     .limit(10)
+    ///     .execute(&conn)
+    ///     .await?;
+    /// ```
+    fn select() -> SelectQuery<Self> {
+        SelectQuery::new()
+    }
+
+    /// number of items matching the filter
+    async fn count<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Option<Filter<Self>>,
+    ) -> Result<usize, surrealdb::Error> {
+        let query = Self::select();
+        match filter {
+            Some(filter) => query.filter(filter).count(conn).await,
+            None => query.count(conn).await,
+        }
+    }
+
+    /// any item matches the filter
+    async fn exists<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Option<Filter<Self>>,
+    ) -> Result<bool, surrealdb::Error> {
+        let query = Self::select();
+        match filter {
+            Some(filter) => query.filter(filter).exists(conn).await,
+            None => query.exists(conn).await,
+        }
+    }
+
+    /// merges `patch` into every item matching the filter in one statement,
+    /// returns the updated items, see [BulkQuery] for other returns
+    fn update_where<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Filter<Self>,
+        patch: impl SurrealValue,
+    ) -> BulkQuery<'_, C, Self> {
+        BulkQuery::update(conn, filter, patch)
+    }
+
+    /// deletes every item matching the filter in one statement,
+    /// returns the deleted items, see [BulkQuery] for other returns
+    fn delete_where<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Filter<Self>,
+    ) -> BulkQuery<'_, C, Self> {
+        BulkQuery::delete(conn, filter)
+    }
+
+    /// every item in the table, queried in batches of `batch_size` by id
+    fn stream_all<C: Connection>(
+        conn: &Surreal<C>,
+        batch_size: usize,
+    ) -> impl Stream<Item = Result<RecordData<Self>, surrealdb::Error>> + Send + 'static
+    where
+        Self: Send,
+    {
+        Self::select().stream(conn, batch_size)
+    }
+
+    /// one page of the table, see [SelectQuery::paginate] to filter it
+    async fn paginate<C: Connection>(
+        conn: &Surreal<C>,
+        request: PageRequest,
+    ) -> Result<Page<RecordData<Self>>, surrealdb::Error> {
+        Self::select().paginate(conn, request).await
+    }
+
+    /// subscribes to a LIVE query on the table, `filter` is the WHERE condition
+    async fn live<C: Connection>(
+        conn: &Surreal<C>,
+        filter: Option<Filter<Self>>,
+    ) -> Result<LiveStream<Self>, surrealdb::Error>
+    where
+        Self: Send,
+    {
+        LiveStream::new(conn, filter).await
+    }
+
+    /// returns functions for register,
+    /// dependencies are checked against the whole set by [define_order](crate::schema::define_order)
     fn register() -> Result<Register, String> {
-        // TODO: add check
-        // for typ in types {
-        //     if does_impl!(typ: surrealdb_extras::SurrealTableInfo) {
-        //         return Err(format!("{} is a table", typ.to_string())
-        //     }
-        // }
-        Ok((Self::name, Self::path, Self::funcs))
+        Ok(Register::of::<Self>())
     }
 }
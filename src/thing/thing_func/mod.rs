@@ -9,13 +9,21 @@ use surrealdb::{
     types::{RecordId, RecordIdKey, SurrealValue},
 };
 
-use crate::{Record, RecordData, SurrealSelectInfo};
+use crate::{Record, RecordData, SurrealKey, SurrealSelectInfo};
 
 #[derive(Clone, Debug, PartialEq, PartialOrd, SurrealValue)]
 /// some usefull functions for Thing
 /// ```
-/// #[derive(surrealdb_extras::SurrealTable, serde::Serialize, serde::Deserialize)]
-/// #[db("test_table")]
+/// use surrealdb::types::SurrealValue;
+///
+/// #[derive(
+///     Clone,
+///     surrealdb_extras::SurrealTable,
+///     SurrealValue,
+///     serde::Serialize,
+///     serde::Deserialize,
+/// )]
+/// #[table(db = test_table)]
 /// struct Test {
 ///     name: String,
 ///     /// a refrence to another table entry
@@ -96,4 +104,14 @@ impl RecordIdFunc {
     pub fn id(&self) -> &RecordIdKey {
         &self.0.key
     }
+
+    /// From table and compound key
+    pub fn from_key(table: impl Into<String>, key: impl SurrealKey) -> Self {
+        Self(RecordId::new(table.into(), key.into_key()))
+    }
+
+    /// parses id into compound key
+    pub fn key_as<K: SurrealKey>(&self) -> Result<K, Error> {
+        K::from_key(self.0.key.clone())
+    }
 }
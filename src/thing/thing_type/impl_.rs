@@ -4,7 +4,7 @@ use std::{
 };
 
 use serde::{Deserialize, Serialize};
-use surrealdb::types::RecordId;
+use surrealdb::types::{Kind, RecordId, SurrealValue, Value};
 
 use crate::{RecordIdFunc, RecordIdType};
 
@@ -35,3 +35,24 @@ impl<'de, T> Deserialize<'de> for RecordIdType<T> {
         })
     }
 }
+
+impl<T> SurrealValue for RecordIdType<T> {
+    fn kind_of() -> Kind {
+        RecordIdFunc::kind_of()
+    }
+
+    fn is_value(value: &Value) -> bool {
+        RecordIdFunc::is_value(value)
+    }
+
+    fn into_value(self) -> Value {
+        self.thing.into_value()
+    }
+
+    fn from_value(value: Value) -> surrealdb_types::anyhow::Result<Self> {
+        Ok(Self {
+            thing: RecordIdFunc::from_value(value)?,
+            parse_to: Default::default(),
+        })
+    }
+}
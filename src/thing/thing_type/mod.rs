@@ -1,23 +1,34 @@
 mod from;
 mod impl_;
 
-use std::marker::PhantomData;
+use std::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+};
 
 use serde::Serialize;
 use surrealdb::{
     Connection, Error, Surreal,
-    method::{Content, Delete, Merge, Patch},
+    method::{Content, Delete, Merge, Patch, Select},
     opt::PatchOp,
-    types::{RecordId, RecordIdKey},
+    types::{RecordId, RecordIdKey, RecordIdKeyRange},
 };
 use surrealdb_types::SurrealValue;
 
-use crate::{RecordData, RecordIdFunc, SurrealSelectInfo, SurrealTableInfo};
+use crate::{RecordData, RecordIdFunc, SurrealKey, SurrealSelectInfo, SurrealTableInfo};
 
 /// RecordIdFunc + defining the table for SurrealTableInfo
 /// ```
-/// #[derive(surrealdb_extras::SurrealTable, serde::Serialize, serde::Deserialize)]
-/// #[db("test_table")]
+/// use surrealdb::types::SurrealValue;
+///
+/// #[derive(
+///     Clone,
+///     surrealdb_extras::SurrealTable,
+///     SurrealValue,
+///     serde::Serialize,
+///     serde::Deserialize,
+/// )]
+/// #[table(db = test_table)]
 /// struct Test {
 ///     name: String,
 ///     /// a refrence to another entry in the table `test_table`
@@ -51,6 +62,29 @@ impl<T: SurrealTableInfo + SurrealSelectInfo> RecordIdType<T> {
             parse_to: Default::default(),
         }
     }
+    /// From compound key in the table of T
+    pub fn from_key(key: impl SurrealKey) -> Self {
+        Self::new(RecordIdFunc::from_key(T::name(), key))
+    }
+
+    /// parses id into compound key
+    pub fn key_as<K: SurrealKey>(&self) -> Result<K, Error> {
+        self.thing.key_as()
+    }
+
+    /// selects every record with a key in range (`table:[a, ..]..[a, ..]`)
+    pub fn range<C: Connection, K: SurrealKey + Clone>(
+        conn: &'_ Surreal<C>,
+        range: impl RangeBounds<K>,
+    ) -> Select<'_, C, Vec<RecordData<T>>> {
+        let bound = |bound: Bound<&K>| bound.cloned().map(SurrealKey::into_key);
+
+        conn.select(T::name()).range(RecordIdKeyRange {
+            start: bound(range.start_bound()),
+            end: bound(range.end_bound()),
+        })
+    }
+
     pub async fn get_part<C: Connection, TT: SurrealValue + SurrealSelectInfo>(
         self,
         conn: &Surreal<C>,
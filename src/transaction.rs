@@ -0,0 +1,366 @@
+use serde::Serialize;
+use surrealdb::{
+    Connection, IndexedResults, Surreal,
+    types::{SurrealValue, Variables},
+};
+
+use crate::{
+    RecordData, RecordIdType, SurrealQuery, SurrealTableInfo, query::QueryBatch, sql,
+    take_statement,
+};
+
+/// queues queries into one `BEGIN TRANSACTION; ... COMMIT TRANSACTION;` request,
+/// returns every `Output` after commit
+/// ```ignore
+/// let (report, created) = conn
+///     .transaction()
+///     .query(Report { min: 3 })
+///     .create(Test::default())
+///     .commit()
+///     .await?;
+/// ```
+#[must_use = "transactions do nothing unless committed"]
+pub struct Transaction<'a, DB, Q = ()> {
+    db: &'a DB,
+    queries: Q,
+}
+
+impl<'a, DB> Transaction<'a, DB> {
+    pub(crate) fn new(db: &'a DB) -> Self {
+        Self { db, queries: () }
+    }
+}
+
+impl<'a, DB, Q> Transaction<'a, DB, Q> {
+    /// queues a query, its variables are renamed to `$q{index}_{var}`
+    pub fn query<N: SurrealQuery>(self, query: N) -> Transaction<'a, DB, Q::Pushed>
+    where
+        Q: Push<N>,
+    {
+        Transaction {
+            db: self.db,
+            queries: self.queries.push(query),
+        }
+    }
+
+    /// queues the creation of an item
+    pub fn create<T: SurrealTableInfo>(self, item: T) -> Transaction<'a, DB, Q::Pushed>
+    where
+        Q: Push<CreateQuery<T>>,
+    {
+        self.query(CreateQuery(item))
+    }
+
+    /// queues merging data into an item
+    pub fn merge<T, D>(self, id: RecordIdType<T>, data: D) -> Transaction<'a, DB, Q::Pushed>
+    where
+        T: SurrealTableInfo,
+        D: SurrealValue + Serialize,
+        Q: Push<MergeQuery<T, D>>,
+    {
+        self.query(MergeQuery(id, data))
+    }
+
+    /// queues the deletion of an item
+    pub fn delete<T: SurrealTableInfo>(self, id: RecordIdType<T>) -> Transaction<'a, DB, Q::Pushed>
+    where
+        Q: Push<DeleteQuery<T>>,
+    {
+        self.query(DeleteQuery(id))
+    }
+}
+
+impl<C, Q> Transaction<'_, Surreal<C>, Q>
+where
+    C: Connection,
+    Q: TransactionQueries,
+{
+    /// runs every queued query in one transaction
+    pub async fn commit(self) -> surrealdb::Result<Q::Output> {
+        let mut batch = QueryBatch::new("BEGIN TRANSACTION;\n".to_string(), 1);
+        self.queries.build(&mut batch);
+        batch.push_str("COMMIT TRANSACTION;");
+
+        let mut res = batch.run(self.db).await?;
+
+        Q::take(&mut res, &batch.offsets)
+    }
+}
+
+/// queries of a transaction, implemented for tuples of [SurrealQuery]
+pub trait TransactionQueries {
+    type Output;
+
+    #[doc(hidden)]
+    fn build(self, batch: &mut QueryBatch);
+
+    #[doc(hidden)]
+    fn take(res: &mut IndexedResults, offsets: &[usize]) -> surrealdb::Result<Self::Output>;
+}
+
+/// appends a query to a tuple of queries
+pub trait Push<N> {
+    type Pushed;
+
+    fn push(self, query: N) -> Self::Pushed;
+}
+
+impl TransactionQueries for () {
+    type Output = ();
+
+    fn build(self, _: &mut QueryBatch) {}
+
+    fn take(_: &mut IndexedResults, _: &[usize]) -> surrealdb::Result<Self::Output> {
+        Ok(())
+    }
+}
+
+impl<N: SurrealQuery> Push<N> for () {
+    type Pushed = (N,);
+
+    fn push(self, query: N) -> Self::Pushed {
+        (query,)
+    }
+}
+
+macro_rules! impl_transaction_queries {
+    ($(($($q:ident: $i:tt),+)),+ $(,)?) => {
+        $(
+            impl<$($q: SurrealQuery),+> TransactionQueries for ($($q,)+) {
+                type Output = ($($q::Output,)+);
+
+                fn build(self, batch: &mut QueryBatch) {
+                    $(batch.push(self.$i);)+
+                }
+
+                fn take(
+                    res: &mut IndexedResults,
+                    offsets: &[usize],
+                ) -> surrealdb::Result<Self::Output> {
+                    Ok(($($q::take_output(res, offsets[$i])?,)+))
+                }
+            }
+
+            impl<$($q: SurrealQuery,)+ N: SurrealQuery> Push<N> for ($($q,)+) {
+                type Pushed = ($($q,)+ N,);
+
+                fn push(self, query: N) -> Self::Pushed {
+                    ($(self.$i,)+ query,)
+                }
+            }
+        )+
+    };
+}
+
+impl_transaction_queries!(
+    (A: 0),
+    (A: 0, B: 1),
+    (A: 0, B: 1, C: 2),
+    (A: 0, B: 1, C: 2, D: 3),
+    (A: 0, B: 1, C: 2, D: 3, E: 4),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10),
+    (A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11),
+);
+
+/// creates an item, returns the record
+pub struct CreateQuery<T>(pub T);
+
+impl<T: SurrealTableInfo> SurrealQuery for CreateQuery<T> {
+    type Output = Option<RecordData<T>>;
+    type Error = surrealdb::Error;
+
+    const QUERY_STR: &'static str = sql!("CREATE type::table($table) CONTENT $content");
+    const STATEMENTS: usize = 1;
+
+    fn into_parts(self) -> (&'static str, Variables) {
+        let mut vars = Variables::new();
+        vars.insert("table", T::name());
+        vars.insert("content", self.0);
+        (Self::QUERY_STR, vars)
+    }
+
+    fn take_output(res: &mut IndexedResults, offset: usize) -> surrealdb::Result<Self::Output> {
+        take_statement(res, offset)
+    }
+}
+
+/// merges data into an item, returns the record
+pub struct MergeQuery<T, D>(pub RecordIdType<T>, pub D);
+
+impl<T: SurrealTableInfo, D: SurrealValue + Serialize> SurrealQuery for MergeQuery<T, D> {
+    type Output = Option<RecordData<T>>;
+    type Error = surrealdb::Error;
+
+    const QUERY_STR: &'static str = sql!("UPDATE $id MERGE $data");
+    const STATEMENTS: usize = 1;
+
+    fn into_parts(self) -> (&'static str, Variables) {
+        let mut vars = Variables::new();
+        vars.insert("id", self.0);
+        vars.insert("data", self.1);
+        (Self::QUERY_STR, vars)
+    }
+
+    fn take_output(res: &mut IndexedResults, offset: usize) -> surrealdb::Result<Self::Output> {
+        take_statement(res, offset)
+    }
+}
+
+/// deletes an item, returns its data before deletion
+pub struct DeleteQuery<T>(pub RecordIdType<T>);
+
+impl<T: SurrealTableInfo> SurrealQuery for DeleteQuery<T> {
+    type Output = Option<T>;
+    type Error = surrealdb::Error;
+
+    const QUERY_STR: &'static str = sql!("DELETE $id RETURN BEFORE");
+    const STATEMENTS: usize = 1;
+
+    fn into_parts(self) -> (&'static str, Variables) {
+        let mut vars = Variables::new();
+        vars.insert("id", self.0);
+        (Self::QUERY_STR, vars)
+    }
+
+    fn take_output(res: &mut IndexedResults, offset: usize) -> surrealdb::Result<Self::Output> {
+        take_statement(res, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use surrealdb::types::SurrealValue;
+
+    use crate::{
+        RecordIdType, SurrealExt, SurrealQuery, SurrealTable, SurrealTableInfo, test_conn,
+    };
+
+    #[derive(
+        Clone, Debug, PartialEq, SurrealTable, SurrealValue, serde::Serialize, serde::Deserialize,
+    )]
+    #[table(db = account)]
+    struct Account {
+        name: String,
+        balance: i64,
+    }
+
+    #[derive(SurrealValue, serde::Serialize)]
+    struct Balance {
+        balance: i64,
+    }
+
+    #[derive(SurrealQuery)]
+    #[query(
+        sql = "SELECT VALUE name FROM account WHERE balance >= {min} ORDER BY name",
+        output = "Vec<String>"
+    )]
+    struct Rich {
+        min: i64,
+    }
+
+    #[derive(SurrealQuery)]
+    #[query(sql = "THROW {message}")]
+    struct Fail {
+        message: String,
+    }
+
+    // `RETURN` would end the transaction
+    #[derive(SurrealQuery)]
+    #[query(sql = "{value} -- the value", output = "Option<i64>")]
+    struct Commented {
+        value: i64,
+    }
+
+    fn account(name: &str, balance: i64) -> Account {
+        Account {
+            name: name.to_string(),
+            balance,
+        }
+    }
+
+    async fn conn_with_accounts() -> surrealdb::Surreal<surrealdb::engine::local::Db> {
+        let conn = test_conn().await;
+        conn.query("CREATE account:bob SET name = 'bob', balance = 5; CREATE account:carol SET name = 'carol', balance = 1;")
+            .await
+            .unwrap()
+            .check()
+            .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn commit_returns_typed_outputs() {
+        let conn = conn_with_accounts().await;
+        let bob = RecordIdType::<Account>::from(("account", "bob"));
+        let carol = RecordIdType::<Account>::from(("account", "carol"));
+
+        let (created, merged, deleted, rich) = conn
+            .transaction()
+            .create(account("alice", 10))
+            .merge(bob, Balance { balance: 20 })
+            .delete(carol)
+            .query(Rich { min: 10 })
+            .commit()
+            .await
+            .unwrap();
+
+        assert_eq!(created.unwrap().data, account("alice", 10));
+        assert_eq!(merged.unwrap().data, account("bob", 20));
+        assert_eq!(deleted, Some(account("carol", 1)));
+        assert_eq!(rich, ["alice", "bob"]);
+    }
+
+    #[tokio::test]
+    async fn failed_statement_rolls_back() {
+        let conn = conn_with_accounts().await;
+        let err = conn
+            .transaction()
+            .create(account("alice", 10))
+            .query(Fail {
+                message: "stop".to_string(),
+            })
+            .commit()
+            .await
+            .unwrap_err();
+
+        assert!(
+            err.to_string().contains("query 1 statement 0 failed"),
+            "{err}"
+        );
+        assert_eq!(Account::select().count(&conn).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn vars_are_namespaced() {
+        let conn = conn_with_accounts().await;
+        let (all, rich) = conn
+            .transaction()
+            .query(Rich { min: 0 })
+            .query(Rich { min: 3 })
+            .commit()
+            .await
+            .unwrap();
+
+        assert_eq!(all, ["bob", "carol"]);
+        assert_eq!(rich, ["bob"]);
+    }
+
+    #[tokio::test]
+    async fn commit_after_comment() {
+        let conn = test_conn().await;
+        let (first, second) = conn
+            .transaction()
+            .query(Commented { value: 1 })
+            .query(Commented { value: 2 })
+            .commit()
+            .await
+            .unwrap();
+
+        assert_eq!((first, second), (Some(1), Some(2)));
+    }
+}
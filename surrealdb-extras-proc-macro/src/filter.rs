@@ -0,0 +1,231 @@
+use proc_macro2::TokenStream;
+use quote::{ToTokens, quote, quote_spanned};
+use syn::{
+    Ident, Lit, Token, Type, bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    token,
+};
+
+use crate::table::filter_field_ident;
+
+/// `Table, field > $var && (other == "x" || !(field INSIDE [1, 2]))`
+pub struct FilterInput {
+    table: Type,
+    expr: FilterExpr,
+}
+
+impl Parse for FilterInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let table = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let expr = FilterExpr::parse_or(input)?;
+
+        if !input.is_empty() {
+            return Err(input.error("expected `&&`, `||` or the end of the filter"));
+        }
+
+        Ok(Self { table, expr })
+    }
+}
+
+impl ToTokens for FilterInput {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self { table, expr } = self;
+        let expr = expr.expand(table);
+
+        tokens.extend(quote!({
+            let filter: surrealdb_extras::Filter<#table> = #expr;
+            filter
+        }));
+    }
+}
+
+enum FilterExpr {
+    And(Box<FilterExpr>, Box<FilterExpr>),
+    Or(Box<FilterExpr>, Box<FilterExpr>),
+    Not(Box<FilterExpr>),
+    Compare {
+        field: Ident,
+        op: Ident,
+        value: FilterValue,
+    },
+}
+
+impl FilterExpr {
+    fn peek_keyword(input: ParseStream, keyword: &str) -> bool {
+        input
+            .cursor()
+            .ident()
+            .is_some_and(|(ident, _)| ident.to_string().eq_ignore_ascii_case(keyword))
+    }
+
+    /// `a || b`, `a OR b`
+    fn parse_or(input: ParseStream) -> syn::Result<Self> {
+        let mut expr = Self::parse_and(input)?;
+
+        loop {
+            if input.peek(Token![||]) {
+                input.parse::<Token![||]>()?;
+            } else if Self::peek_keyword(input, "or") {
+                input.call(Ident::parse_any)?;
+            } else {
+                break;
+            }
+
+            expr = Self::Or(Box::new(expr), Box::new(Self::parse_and(input)?));
+        }
+
+        Ok(expr)
+    }
+
+    /// `a && b`, `a AND b`
+    fn parse_and(input: ParseStream) -> syn::Result<Self> {
+        let mut expr = Self::parse_unary(input)?;
+
+        loop {
+            if input.peek(Token![&&]) {
+                input.parse::<Token![&&]>()?;
+            } else if Self::peek_keyword(input, "and") {
+                input.call(Ident::parse_any)?;
+            } else {
+                break;
+            }
+
+            expr = Self::And(Box::new(expr), Box::new(Self::parse_unary(input)?));
+        }
+
+        Ok(expr)
+    }
+
+    /// `!a`, `(a)` or a comparison
+    fn parse_unary(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![!]) {
+            input.parse::<Token![!]>()?;
+            return Ok(Self::Not(Box::new(Self::parse_unary(input)?)));
+        }
+
+        if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            let expr = Self::parse_or(&content)?;
+
+            if !content.is_empty() {
+                return Err(content.error("expected `&&`, `||` or `)`"));
+            }
+
+            return Ok(expr);
+        }
+
+        let field = input.call(Ident::parse_any)?;
+        let op = Self::parse_op(input)?;
+        let value = input.parse()?;
+
+        Ok(Self::Compare { field, op, value })
+    }
+
+    /// comparison operator as the `Field` method
+    fn parse_op(input: ParseStream) -> syn::Result<Ident> {
+        let span = input.span();
+
+        let method = if input.peek(Token![==]) {
+            input.parse::<Token![==]>()?;
+            "eq"
+        } else if input.peek(Token![!=]) {
+            input.parse::<Token![!=]>()?;
+            "ne"
+        } else if input.peek(Token![>=]) {
+            input.parse::<Token![>=]>()?;
+            "gte"
+        } else if input.peek(Token![<=]) {
+            input.parse::<Token![<=]>()?;
+            "lte"
+        } else if input.peek(Token![>]) {
+            input.parse::<Token![>]>()?;
+            "gt"
+        } else if input.peek(Token![<]) {
+            input.parse::<Token![<]>()?;
+            "lt"
+        } else if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            "eq"
+        } else if Self::peek_keyword(input, "contains") {
+            input.call(Ident::parse_any)?;
+            "contains"
+        } else if Self::peek_keyword(input, "inside") || Self::peek_keyword(input, "in") {
+            input.call(Ident::parse_any)?;
+            "inside"
+        } else {
+            return Err(input.error(
+                "expected one of `==`, `=`, `!=`, `>`, `>=`, `<`, `<=`, `CONTAINS`, `INSIDE`",
+            ));
+        };
+
+        Ok(Ident::new(method, span))
+    }
+
+    fn expand(&self, table: &Type) -> TokenStream {
+        match self {
+            Self::And(left, right) => {
+                let (left, right) = (left.expand(table), right.expand(table));
+                quote!((#left).and(#right))
+            }
+            Self::Or(left, right) => {
+                let (left, right) = (left.expand(table), right.expand(table));
+                quote!((#left).or(#right))
+            }
+            Self::Not(expr) => {
+                let expr = expr.expand(table);
+                quote!(!(#expr))
+            }
+            Self::Compare { field, op, value } => {
+                // an unknown field fails on the hidden constant with the span of the field
+                let field = filter_field_ident(field);
+                let field = quote_spanned!(field.span()=> <#table>::#field);
+
+                quote!(#field.#op(#value))
+            }
+        }
+    }
+}
+
+/// `$var` from the surrounding scope, a literal or an array of values
+enum FilterValue {
+    Var(Ident),
+    Lit(TokenStream),
+    Array(Punctuated<FilterValue, Token![,]>),
+}
+
+impl Parse for FilterValue {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![$]) {
+            input.parse::<Token![$]>()?;
+            return Ok(Self::Var(input.parse()?));
+        }
+
+        if input.peek(token::Bracket) {
+            let content;
+            bracketed!(content in input);
+            return Ok(Self::Array(Punctuated::parse_terminated(&content)?));
+        }
+
+        let neg = input.parse::<Option<Token![-]>>()?;
+        let lit = input
+            .parse::<Lit>()
+            .map_err(|err| syn::Error::new(err.span(), "expected `$var`, a literal or `[...]`"))?;
+
+        Ok(Self::Lit(quote!(#neg #lit)))
+    }
+}
+
+impl ToTokens for FilterValue {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Self::Var(var) => var.to_tokens(tokens),
+            Self::Lit(lit) => lit.to_tokens(tokens),
+            Self::Array(values) => tokens.extend(quote!([#values])),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use darling::{
+    FromDeriveInput, FromField,
+    ast::{Data, Style},
+    util::Ignored,
+};
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Generics, Ident, Index, LitStr, Type};
+
+use crate::util::DeriveInputUtil;
+
+#[derive(FromDeriveInput)]
+#[darling(supports(struct_named, struct_tuple))]
+pub struct SurrealKey {
+    ident: Ident,
+    generics: Generics,
+    data: Data<Ignored, SurrealKeyField>,
+}
+
+impl DeriveInputUtil for SurrealKey {
+    fn gen_(&self) -> manyhow::Result<TokenStream> {
+        let Self {
+            ident,
+            generics,
+            data,
+        } = self;
+
+        let fields = match data {
+            Data::Enum(_) => unreachable!(),
+            Data::Struct(fields) => fields,
+        };
+
+        let (impl_gen, ty_gen, where_gen) = generics.split_for_impl();
+
+        let (into_key, from_key) = match fields.style {
+            Style::Tuple => {
+                let len = fields.len();
+                let indices = (0..len).map(Index::from);
+                let names = (0..len).map(|ind| LitStr::new(&ind.to_string(), ident.span()));
+                let tys = fields.iter().map(|f| &f.ty);
+
+                (
+                    quote! {
+                        surrealdb::types::RecordIdKey::Array(
+                            surrealdb::types::Array::from_values(vec![
+                                #( surrealdb::types::SurrealValue::into_value(self.#indices) ),*
+                            ])
+                        )
+                    },
+                    quote! {
+                        let mut values = surrealdb_extras::key_array(key, #len)?.into_iter();
+                        Ok(Self(#( surrealdb_extras::key_part::<#tys>(values.next(), #names)? ),*))
+                    },
+                )
+            }
+            _ => {
+                let idents = fields
+                    .iter()
+                    .map(|f| f.ident.as_ref().unwrap())
+                    .collect::<Vec<_>>();
+                let names = idents
+                    .iter()
+                    .map(|ident| LitStr::new(&ident.to_string(), ident.span()))
+                    .collect::<Vec<_>>();
+                let tys = fields.iter().map(|f| &f.ty);
+
+                (
+                    quote! {
+                        let mut obj = surrealdb::types::Object::new();
+                        #( obj.insert(#names, self.#idents); )*
+                        surrealdb::types::RecordIdKey::Object(obj)
+                    },
+                    quote! {
+                        let mut obj = surrealdb_extras::key_object(key)?;
+                        Ok(Self {
+                            #( #idents: surrealdb_extras::key_part::<#tys>(obj.remove(#names), #names)? ),*
+                        })
+                    },
+                )
+            }
+        };
+
+        Ok(quote! {
+            impl #impl_gen surrealdb_extras::SurrealKey for #ident #ty_gen #where_gen {
+                fn into_key(self) -> surrealdb::types::RecordIdKey {
+                    #into_key
+                }
+
+                fn from_key(
+                    key: surrealdb::types::RecordIdKey
+                ) -> Result<Self, surrealdb::Error> {
+                    #from_key
+                }
+            }
+        })
+    }
+}
+
+#[derive(FromField)]
+struct SurrealKeyField {
+    ident: Option<Ident>,
+    ty: Type,
+}
@@ -1,21 +1,25 @@
+mod filter;
+mod key;
+mod migration;
 mod query;
 mod table;
 mod util;
 
 use proc_macro2::TokenStream;
-use quote::ToTokens;
-use surrealdb_core::dbs::{Capabilities, capabilities::Targets};
+use quote::{ToTokens, quote};
 use syn::LitStr;
 
 use crate::{
+    filter::FilterInput,
+    key::SurrealKey,
     query::SurrealQuery,
     table::{SurrealSelect, SurrealTable},
-    util::DeriveInputUtil,
+    util::{DeriveInputUtil, check_sql, read_manifest_file},
 };
 
 /// implements SurrealSelectInfo, SurrealTableInfo, add and insert
 #[manyhow::manyhow]
-#[proc_macro_derive(SurrealTable, attributes(table))]
+#[proc_macro_derive(SurrealTable, attributes(table, opt))]
 pub fn table(input: TokenStream) -> manyhow::Result<TokenStream> {
     let table = SurrealTable::parse(input)?;
     table.gen_()
@@ -23,25 +27,65 @@ pub fn table(input: TokenStream) -> manyhow::Result<TokenStream> {
 
 /// implements SurrealSelectInfo
 #[manyhow::manyhow]
-#[proc_macro_derive(SurrealSelect)]
+#[proc_macro_derive(SurrealSelect, attributes(opt))]
 pub fn select(input: TokenStream) -> manyhow::Result<TokenStream> {
     let select = SurrealSelect::parse(input)?;
     select.gen_()
 }
 
+/// implements SurrealKey (tuple structs as array keys, named structs as object keys)
+#[manyhow::manyhow]
+#[proc_macro_derive(SurrealKey)]
+pub fn key(input: TokenStream) -> manyhow::Result<TokenStream> {
+    let key = SurrealKey::parse(input)?;
+    key.gen_()
+}
+
 #[manyhow::manyhow]
 #[proc_macro]
 pub fn sql(input: TokenStream) -> manyhow::Result<TokenStream> {
     let sql_lit_str = syn::parse2::<LitStr>(input)?;
-    let sql_str = sql_lit_str.value();
+    check_sql(&sql_lit_str.value(), sql_lit_str.span())?;
+
+    Ok(sql_lit_str.to_token_stream())
+}
+
+/// `include_str!` for `.surql` files (relative to `CARGO_MANIFEST_DIR`), checked like `sql!`
+#[manyhow::manyhow]
+#[proc_macro]
+pub fn include_surql(input: TokenStream) -> manyhow::Result<TokenStream> {
+    let path = syn::parse2::<LitStr>(input)?;
+    let (full_path, sql_str) = read_manifest_file(&path)?;
+    check_sql(&sql_str, path.span())?;
 
-    let mut capabilities = Capabilities::all();
-    *capabilities.allowed_experimental_features_mut() = Targets::All;
+    Ok(quote!(include_str!(#full_path)))
+}
+
+/// `Vec<Migration>` with the `{version}_{name}.up.surql` and `{version}_{name}.down.surql` files
+/// of a directory (relative to `CARGO_MANIFEST_DIR`) included and checked like `sql!`
+/// ```ignore
+/// let migrator = Migrator::new(embed_migrations!("migrations"))?;
+/// ```
+#[manyhow::manyhow]
+#[proc_macro]
+pub fn embed_migrations(input: TokenStream) -> manyhow::Result<TokenStream> {
+    let dir = syn::parse2::<LitStr>(input)?;
+    migration::embed_migrations(&dir)
+}
+
+/// typed `Filter` for a `SurrealTable`, field names are the db names and checked at compile time
+/// ```ignore
+/// let min = 5;
+/// let filter = filter!(Test, random_number > $min && new_name CONTAINS "x");
+/// ```
+/// values are `$var` from the surrounding scope, literals or `[...]`,
+/// operators are `== != > >= < <= CONTAINS INSIDE`, combined with `&& || !` and parentheses
+#[manyhow::manyhow]
+#[proc_macro]
+pub fn filter(input: TokenStream) -> manyhow::Result<TokenStream> {
+    let filter = syn::parse2::<FilterInput>(input)?;
 
-    match surrealdb_core::syn::parse_with_capabilities(&sql_str, &capabilities) {
-        Ok(_) => Ok(sql_lit_str.to_token_stream()),
-        Err(err) => manyhow::bail!(sql_lit_str.span(), "{err}"),
-    }
+    Ok(filter.to_token_stream())
 }
 
 #[manyhow::manyhow]
@@ -0,0 +1,82 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::LitStr;
+
+use crate::util::parse_sql;
+
+/// `Vec<Migration>` from the `{version}_{name}.up.surql`/`.down.surql` files in `dir`
+pub fn embed_migrations(dir: &LitStr) -> manyhow::Result<TokenStream> {
+    let span = dir.span();
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|err| manyhow::error_message!(span, "CARGO_MANIFEST_DIR: {err}"))?;
+    let full_dir = PathBuf::from(manifest_dir).join(dir.value());
+
+    let entries = std::fs::read_dir(&full_dir).map_err(|err| {
+        manyhow::error_message!(span, "failed to read {}: {err}", full_dir.display())
+    })?;
+
+    let mut migrations = BTreeMap::<u64, (String, Option<LitStr>, Option<LitStr>)>::new();
+
+    for entry in entries {
+        let path = entry
+            .map_err(|err| manyhow::error_message!(span, "{err}"))?
+            .path();
+        let Some(file) = path.file_name().and_then(|file| file.to_str()) else {
+            continue;
+        };
+
+        let (stem, up) = match file.strip_suffix(".up.surql") {
+            Some(stem) => (stem, true),
+            None => match file.strip_suffix(".down.surql") {
+                Some(stem) => (stem, false),
+                None if file.ends_with(".surql") => {
+                    manyhow::bail!(
+                        span,
+                        "{file} is not named {{version}}_{{name}}.up.surql or {{version}}_{{name}}.down.surql"
+                    );
+                }
+                None => continue,
+            },
+        };
+        let Some((version, name)) = stem
+            .split_once('_')
+            .and_then(|(version, name)| Some((version.parse::<u64>().ok()?, name)))
+        else {
+            manyhow::bail!(span, "{file} does not start with `{{version}}_`");
+        };
+
+        let script = std::fs::read_to_string(&path)
+            .map_err(|err| manyhow::error_message!(span, "failed to read {file}: {err}"))?;
+        if let Err(err) = parse_sql(&script) {
+            manyhow::bail!(span, "{file}: {err}");
+        }
+
+        let (_, up_script, down_script) = migrations
+            .entry(version)
+            .or_insert_with(|| (name.to_string(), None, None));
+        let slot = if up { up_script } else { down_script };
+        let path = LitStr::new(&path.to_string_lossy(), span);
+
+        if slot.replace(path).is_some() {
+            manyhow::bail!(span, "migration {version} exists twice");
+        }
+    }
+
+    let migrations = migrations
+        .into_iter()
+        .map(|(version, (name, up, down))| {
+            let Some(up) = up else {
+                manyhow::bail!(span, "{version}_{name}.up.surql is missing");
+            };
+            let down = down.map(|down| quote!(.with_down(include_str!(#down))));
+
+            Ok(quote! {
+                surrealdb_extras::migration::Migration::new(#version, #name, include_str!(#up)) #down
+            })
+        })
+        .collect::<manyhow::Result<Vec<_>>>()?;
+
+    Ok(quote!(vec![#( #migrations ),*]))
+}
@@ -1,15 +1,24 @@
-use std::str::FromStr;
+use std::{ops::Range, str::FromStr};
 
 use darling::{
-    FromDeriveInput, FromField,
+    FromDeriveInput, FromField, FromMeta,
     ast::{Data, Style},
     util::{Flag, Ignored},
 };
 use proc_macro2::{Span, TokenStream};
-use quote::{ToTokens, quote};
-use syn::{Generics, Ident, LitStr, Path, Type, Visibility, spanned::Spanned};
+use quote::{ToTokens, quote, quote_spanned};
+use surrealdb_core::syn::{
+    lexer::Lexer,
+    token::{Delim, TokenKind},
+};
+use syn::{
+    Expr, ExprLit, Generics, Ident, Lit, LitInt, LitStr, Meta, Path, Token, Type, Visibility,
+    parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    spanned::Spanned,
+};
 
-use crate::DeriveInputUtil;
+use crate::{DeriveInputUtil, check_sql, read_manifest_file};
 
 #[derive(FromDeriveInput)]
 #[darling(supports(struct_named, struct_tuple, struct_unit), attributes(query))]
@@ -18,64 +27,371 @@ pub struct SurrealQuery {
     generics: Generics,
     data: Data<Ignored, DBQueryField>,
 
-    output: Option<LitStr>,
+    output: Option<QueryOutput>,
     stream: Option<LitStr>,
     check: Flag,
     error: Option<Path>,
-    sql: LitStr,
+    sql: Option<LitStr>,
+    file: Option<LitStr>,
 }
 
 impl SurrealQuery {
-    fn build_query(&self) -> manyhow::Result<TokenStream> {
+    /// body of `take_output`, takes the output of the query's `statements`
+    /// from the result, starting at the `offset` argument of the generated function
+    fn build_take_output(&self, statements: usize) -> manyhow::Result<TokenStream> {
         let Self {
-            data,
+            output,
             check,
             stream,
             ..
         } = self;
 
-        let fields = match data {
-            Data::Enum(_) => unreachable!(),
-            Data::Struct(fields) => fields,
-        };
-
-        let binds = DBQueryField::build_query_binds(&fields.fields);
-
-        let res = quote! {
-            db.query(Self::QUERY_STR)
-                #(#binds)*
-                .await?
-        };
-
         Ok(match stream {
             Some(stream) => {
                 let stream = TokenStream::from_str(&stream.value())
                     .map_err(|err| manyhow::error_message!(stream.span(), "{err}"))?;
-                quote!(Ok(#res.stream::<surrealdb::Notification<#stream>>(0)?))
+                quote!(Ok(res.stream::<surrealdb::Notification<#stream>>(offset)?))
             }
-            None => match check.is_present() {
-                true => quote! {
-                    #res.check()?;
+            None => match (check.is_present(), output) {
+                // without an output every statement is checked
+                (true, _) | (false, None) => quote! {
+                    for ind in offset..offset + Self::STATEMENTS {
+                        surrealdb_extras::take_statement::<surrealdb::types::Value>(res, ind)?;
+                    }
                     Ok(())
                 },
-                false => quote!(Ok(#res.take::<Self::Output>(0)?)),
+                (false, Some(QueryOutput::Indexed(outputs))) => {
+                    let mut outputs = outputs
+                        .iter()
+                        .map(|(ind, ty)| Ok((ind.base10_parse::<usize>()?, ind, ty)))
+                        .collect::<manyhow::Result<Vec<_>>>()?;
+
+                    if let Some((_, ind, _)) = outputs.iter().find(|(i, ..)| *i >= statements) {
+                        manyhow::bail!(ind.span(), "the query only has {statements} statements");
+                    }
+
+                    let vars = outputs
+                        .iter()
+                        .map(|(i, ..)| Ident::new(&format!("output{i}"), Span::mixed_site()))
+                        .collect::<Vec<_>>();
+
+                    outputs.sort_by_key(|(i, ..)| *i);
+
+                    // every statement is taken in order, so the first failed one is reported
+                    let takes = (0..statements)
+                        .map(|i| match outputs.iter().find(|(ind, ..)| *ind == i) {
+                            Some((_, _, ty)) => {
+                                let ty = QueryOutput::parse_ty(ty)?;
+                                let var = Ident::new(&format!("output{i}"), Span::mixed_site());
+
+                                Ok(quote! {
+                                    let #var = surrealdb_extras::take_statement::<#ty>(
+                                        res,
+                                        offset + #i
+                                    )?;
+                                })
+                            }
+                            None => Ok(quote! {
+                                surrealdb_extras::take_statement::<surrealdb::types::Value>(
+                                    res,
+                                    offset + #i
+                                )?;
+                            }),
+                        })
+                        .collect::<manyhow::Result<Vec<_>>>()?;
+
+                    quote! {
+                        #(#takes)*
+                        Ok((#(#vars),*))
+                    }
+                }
+                (false, Some(QueryOutput::Single(_))) => {
+                    quote!(surrealdb_extras::take_statement::<Self::Output>(
+                        res, offset
+                    ))
+                }
             },
         })
     }
 
-    fn build_query_str(&self) -> LitStr {
+    /// inline `sql` or the content of `file` (relative to `CARGO_MANIFEST_DIR`),
+    /// with the absolute path of the file to track it
+    fn load_sql(&self) -> manyhow::Result<(LitStr, Option<LitStr>)> {
+        match (&self.sql, &self.file) {
+            (Some(sql), None) => Ok((sql.clone(), None)),
+            (None, Some(file)) => {
+                let (full_path, sql) = read_manifest_file(file)?;
+                Ok((LitStr::new(&sql, file.span()), Some(full_path)))
+            }
+            (Some(sql), Some(_)) => {
+                manyhow::bail!(sql.span(), "`sql` and `file` are mutually exclusive");
+            }
+            (None, None) => {
+                manyhow::bail!(self.ident.span(), "Missing field `sql` or `file`");
+            }
+        }
+    }
+
+    /// every combination of the optional `[[ ... ]]` fragments,
+    /// indexed by a mask of the included fragments, with the `Option` fields of each fragment
+    fn build_query_variants(
+        &self,
+        sql: &LitStr,
+    ) -> manyhow::Result<(Vec<LitStr>, Vec<Vec<usize>>)> {
         let fields = match &self.data {
             Data::Enum(_) => unreachable!(),
             Data::Struct(fields) => fields,
         };
 
-        let query_str = fields.iter().fold(self.sql.value(), |str, field| {
-            let ident = field.ident.as_ref().unwrap();
-            str.replace(&format!("{{{ident}}}"), &format!("${ident}"))
-        });
+        let span = sql.span();
+        let sql = sql.value();
+        let fragments = fragments(&sql);
+
+        if fragments.len() > MAX_FRAGMENTS {
+            manyhow::bail!(
+                span,
+                "at most {MAX_FRAGMENTS} optional fragments are supported, found {}",
+                fragments.len()
+            );
+        }
+
+        let variants = (0..1usize << fragments.len())
+            .map(|mask| {
+                let mut variant = String::with_capacity(sql.len());
+                let mut last = 0;
+
+                for (ind, range) in fragments.iter().enumerate() {
+                    variant.push_str(&sql[last..range.start]);
+                    if mask & (1 << ind) != 0 {
+                        variant.push_str(&sql[range.start + 2..range.end - 2]);
+                    }
+                    last = range.end;
+                }
+                variant.push_str(&sql[last..]);
+
+                variant
+            })
+            .collect::<Vec<_>>();
+
+        // the variant with every fragment reports all placeholder errors at once
+        let full = self.build_query_str(variants.last().unwrap(), span)?;
+        let conditions = fragments
+            .iter()
+            .map(|range| {
+                let inner = &sql[range.start + 2..range.end - 2];
+                let optional = placeholders(inner)
+                    .filter_map(|(_, name)| {
+                        fields
+                            .iter()
+                            .enumerate()
+                            .find(|(ind, field)| field.placeholder(*ind) == name)
+                            .filter(|(_, field)| field.is_option())
+                            .map(|(ind, _)| ind)
+                    })
+                    .fold(vec![], |mut list, ind| {
+                        if !list.contains(&ind) {
+                            list.push(ind);
+                        }
+                        list
+                    });
+
+                match optional.is_empty() {
+                    true => Err(manyhow::error_message!(
+                        span,
+                        "optional fragment `{}` has no placeholder of an `Option` field",
+                        &sql[range.clone()]
+                    )
+                    .into()),
+                    false => Ok(optional),
+                }
+            })
+            .collect::<manyhow::Result<Vec<_>>>()?;
+
+        let mut query_strs = variants
+            .iter()
+            .take(variants.len() - 1)
+            .map(|variant| self.build_query_str(variant, span))
+            .collect::<manyhow::Result<Vec<_>>>()?;
+        query_strs.push(full);
 
-        LitStr::new(&query_str, self.sql.span())
+        Ok((query_strs, conditions))
     }
+
+    /// replaces `{field}` (or `{0}` for tuple structs) with `$field`,
+    /// unknown placeholders are an error
+    fn build_query_str(&self, sql: &str, span: Span) -> manyhow::Result<LitStr> {
+        let fields = match &self.data {
+            Data::Enum(_) => unreachable!(),
+            Data::Struct(fields) => fields,
+        };
+
+        let mut query_str = String::with_capacity(sql.len());
+        let mut last = 0;
+
+        let mut err_emitter = manyhow::Emitter::new();
+
+        for (range, name) in placeholders(sql) {
+            query_str.push_str(&sql[last..range.start]);
+            last = range.end;
+
+            match fields
+                .iter()
+                .enumerate()
+                .find(|(ind, field)| field.placeholder(*ind) == name)
+            {
+                Some((_, field)) if field.skip.is_present() => {
+                    err_emitter.emit(manyhow::error_message!(
+                        span,
+                        "placeholder `{{{name}}}` refers to a field with `#[var(skip)]`"
+                    ));
+                }
+                Some((ind, field)) if field.table.is_present() => {
+                    query_str.push_str(&format!("type::table(${})", field.var_name(ind)));
+                }
+                Some((ind, field)) => {
+                    query_str.push('$');
+                    query_str.push_str(&field.var_name(ind));
+                }
+                None => {
+                    let known = match fields.is_empty() {
+                        true => "none".to_string(),
+                        false => fields
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, field)| !field.skip.is_present())
+                            .map(|(ind, field)| format!("`{{{}}}`", field.placeholder(ind)))
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                    };
+
+                    err_emitter.emit(manyhow::error_message!(
+                        span,
+                        "unknown placeholder `{{{name}}}`";
+                        help = "known placeholders: {known}"
+                    ));
+                }
+            }
+        }
+        query_str.push_str(&sql[last..]);
+
+        err_emitter.into_result()?;
+
+        Ok(LitStr::new(&query_str, span))
+    }
+
+    /// warns about fields that are neither used as `{field}` nor as `$field`
+    ///
+    /// proc macros can't emit warnings on stable (`proc_macro::Diagnostic` is unstable)
+    /// and an unused field isn't wrong, so an error would be too strict,
+    /// using a `#[deprecated]` const spanned at the field makes rustc warn there instead,
+    /// `#[var(skip)]` silences it
+    fn unused_field_warnings(&self, query_str: &str) -> TokenStream {
+        let fields = match &self.data {
+            Data::Enum(_) => unreachable!(),
+            Data::Struct(fields) => fields,
+        };
+
+        let warnings = fields
+            .iter()
+            .enumerate()
+            .filter(|(ind, field)| {
+                !field.skip.is_present() && !references_var(query_str, &field.var_name(*ind))
+            })
+            .map(|(ind, field)| {
+                let name = field.placeholder(ind);
+                let note = format!(
+                    "field `{name}` is never referenced in the query, use `{{{name}}}` or `${}`",
+                    field.var_name(ind)
+                );
+                let warning = Ident::new(&format!("_unused_query_field_{name}"), field.span());
+
+                quote_spanned! {field.span()=>
+                    #[deprecated(note = #note)]
+                    #[allow(non_upper_case_globals)]
+                    const #warning: () = ();
+                    let _ = #warning;
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match warnings.is_empty() {
+            true => TokenStream::new(),
+            false => quote!(const _: () = { #(#warnings)* };),
+        }
+    }
+}
+
+/// `2^MAX_FRAGMENTS` query strings are generated
+const MAX_FRAGMENTS: usize = 8;
+
+/// every `[[ ... ]]` containing a placeholder, literal nested arrays like `[[1, 2]]`
+/// and brackets in strings or comments are kept
+fn fragments(sql: &str) -> Vec<Range<usize>> {
+    let doubled = |from: usize, kind: TokenKind| {
+        let offsets = token_offsets(sql, kind)
+            .filter(|ind| *ind >= from)
+            .collect::<Vec<_>>();
+        offsets
+            .windows(2)
+            .find(|pair| pair[1] == pair[0] + 1)
+            .map(|pair| pair[0])
+    };
+
+    let mut fragments = vec![];
+    let mut from = 0;
+
+    while let Some(start) = doubled(from, TokenKind::OpenDelim(Delim::Bracket)) {
+        let Some(close) = doubled(start + 2, TokenKind::CloseDelim(Delim::Bracket)) else {
+            break;
+        };
+        let end = close + 2;
+
+        match placeholders(&sql[start + 2..close]).next() {
+            Some(_) => {
+                fragments.push(start..end);
+                from = end;
+            }
+            None => from = start + 1,
+        }
+    }
+
+    fragments
+}
+
+/// every `{name}` without whitespace, where name is an identifier or a tuple index,
+/// outside of strings and comments
+fn placeholders(sql: &str) -> impl Iterator<Item = (Range<usize>, &str)> {
+    token_offsets(sql, TokenKind::OpenDelim(Delim::Brace)).filter_map(|start| {
+        let len = sql[start + 1..].find('}')?;
+        let name = &sql[start + 1..start + 1 + len];
+
+        let is_placeholder = match name.starts_with(|c: char| c.is_ascii_digit()) {
+            true => name.chars().all(|c| c.is_ascii_digit()),
+            false => {
+                !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+            }
+        };
+
+        is_placeholder.then_some((start..start + len + 2, name))
+    })
+}
+
+/// checks for the parameter `$var`, outside of strings and comments
+fn references_var(query_str: &str, var: &str) -> bool {
+    Lexer::new(query_str.as_bytes()).any(|token| {
+        let start = token.span.offset as usize;
+        let end = start + token.span.len as usize;
+
+        token.kind == TokenKind::Parameter && query_str[start + 1..end] == *var
+    })
+}
+
+/// byte offsets of the tokens of `kind`, strings, escaped identifiers and comments
+/// are single tokens of the lexer
+fn token_offsets(sql: &str, kind: TokenKind) -> impl Iterator<Item = usize> {
+    Lexer::new(sql.as_bytes())
+        .filter(move |token| token.kind == kind)
+        .map(|token| token.span.offset as usize)
 }
 
 impl DeriveInputUtil for SurrealQuery {
@@ -98,14 +414,9 @@ impl DeriveInputUtil for SurrealQuery {
                     "surrealdb::method::QueryStream<surrealdb::Notification<{}>>",
                     ty.value()
                 ))
-                .map_err(|err| manyhow::error_message!(ty.span(), "{err}"))
-            })
-            .or_else(|| {
-                output.as_ref().map(|ty| {
-                    TokenStream::from_str(&ty.value())
-                        .map_err(|err| manyhow::error_message!(ty.span(), "{err}"))
-                })
+                .map_err(|err| manyhow::error_message!(ty.span(), "{err}").into())
             })
+            .or_else(|| output.as_ref().map(QueryOutput::output_ty))
             .transpose()?
             .unwrap_or_else(|| quote!(()));
 
@@ -121,14 +432,57 @@ impl DeriveInputUtil for SurrealQuery {
             Data::Struct(fields) => fields,
         };
 
-        let query_str = self.build_query_str();
-        let query = self.build_query()?;
+        let (sql, file) = self.load_sql()?;
+        let (query_strs, conditions) = self.build_query_variants(&sql)?;
+        let query_str = query_strs.last().unwrap();
+        let statements = check_sql(&query_str.value(), query_str.span())?;
+        for variant in &query_strs {
+            let variant_statements = check_sql(&variant.value(), variant.span())?;
+            if variant_statements != statements {
+                manyhow::bail!(
+                    variant.span(),
+                    "optional fragments must not change the number of statements";
+                    note = "`{}` has {variant_statements} instead of {statements}",
+                    variant.value()
+                );
+            }
+        }
+        let warnings = self.unused_field_warnings(&query_str.value());
+        let take_output = self.build_take_output(statements)?;
+        let vars = Ident::new("vars", Span::mixed_site());
+        let binds = DBQueryField::build_query_binds(&fields.fields, &vars);
+
+        let query_select = match conditions.is_empty() {
+            true => quote!(Self::QUERY_STR),
+            false => {
+                let len = query_strs.len();
+                let mask = Ident::new("mask", Span::mixed_site());
+                let included = conditions.iter().enumerate().map(|(bit, optional)| {
+                    let idents = optional
+                        .iter()
+                        .map(|ind| fields.fields[*ind].var_ident(*ind));
+                    quote!(if #(#idents.is_some())&&* { #mask |= 1 << #bit; })
+                });
+
+                quote!({
+                    const QUERIES: [&str; #len] = [#(surrealdb_extras::sql!(#query_strs)),*];
+
+                    let mut #mask = 0usize;
+                    #(#included)*
+                    QUERIES[#mask]
+                })
+            }
+        };
+        let query_select_ident = Ident::new("query", Span::mixed_site());
 
         let field_names = fields.fields.iter().enumerate().map(|(ind, field)| {
-            field
-                .ident
-                .clone()
-                .unwrap_or_else(|| Ident::new(&format!("var{ind}"), field.span()))
+            let var_ident = field.var_ident(ind);
+
+            match (field.skip.is_present(), &field.ident) {
+                (true, Some(ident)) => quote!(#ident: _),
+                (true, None) => quote!(_),
+                (false, _) => var_ident.to_token_stream(),
+            }
         });
 
         let self_unwrapped = {
@@ -141,30 +495,146 @@ impl DeriveInputUtil for SurrealQuery {
             fields.map(|fields| quote!(let Self #fields = self;))
         };
 
+        let track_file = file.map(|file| {
+            quote!(
+                const _: &str = include_str!(#file);
+            )
+        });
+
         Ok(quote! {
+            #warnings
+            #track_file
+
             impl #impl_gen surrealdb_extras::SurrealQuery for #ident #ty_gen #where_gen {
                 type Output = #output;
                 type Error = #error;
 
                 const QUERY_STR: &'static str = surrealdb_extras::sql!(#query_str);
+                const STATEMENTS: usize = #statements;
 
-                async fn execute<C>(
-                    self,
-                    db: surrealdb::Surreal<C>
-                ) -> Result<Self::Output, Self::Error> where C: surrealdb::Connection {
+                fn into_parts(self) -> (&'static str, surrealdb::types::Variables) {
                     #self_unwrapped
-                    #query
+                    let #query_select_ident = #query_select;
+                    let mut #vars = surrealdb::types::Variables::new();
+                    #(#binds)*
+                    (#query_select_ident, #vars)
+                }
+
+                fn take_output(
+                    res: &mut surrealdb::IndexedResults,
+                    offset: usize
+                ) -> surrealdb::Result<Self::Output> {
+                    #take_output
                 }
             }
         })
     }
 }
 
+/// `output = "T"` takes statement 0,
+/// `output(0 = "A", 2 = "B")` takes the listed statements into `(A, B)`
+enum QueryOutput {
+    Single(LitStr),
+    Indexed(Vec<(LitInt, LitStr)>),
+}
+
+impl QueryOutput {
+    fn parse_ty(ty: &LitStr) -> manyhow::Result<TokenStream> {
+        TokenStream::from_str(&ty.value())
+            .map_err(|err| manyhow::error_message!(ty.span(), "{err}").into())
+    }
+
+    fn output_ty(&self) -> manyhow::Result<TokenStream> {
+        match self {
+            Self::Single(ty) => Self::parse_ty(ty),
+            Self::Indexed(outputs) => {
+                let tys = outputs
+                    .iter()
+                    .map(|(_, ty)| Self::parse_ty(ty))
+                    .collect::<manyhow::Result<Vec<_>>>()?;
+
+                Ok(quote!((#(#tys),*)))
+            }
+        }
+    }
+}
+
+impl FromMeta for QueryOutput {
+    fn from_meta(item: &Meta) -> darling::Result<Self> {
+        match item {
+            Meta::NameValue(name_value) => match &name_value.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(ty), ..
+                }) => Ok(Self::Single(ty.clone())),
+                expr => Err(darling::Error::unexpected_expr_type(expr)),
+            },
+            Meta::List(list) => {
+                let outputs = list
+                    .parse_args_with(Punctuated::<IndexedOutput, Token![,]>::parse_terminated)?
+                    .into_iter()
+                    .map(|IndexedOutput { index, ty }| (index, ty))
+                    .collect::<Vec<_>>();
+
+                let mut errors = darling::Error::accumulator();
+
+                if outputs.is_empty() {
+                    errors.push(darling::Error::too_few_items(1).with_span(list));
+                }
+
+                let mut seen = Vec::with_capacity(outputs.len());
+                for (index, _) in &outputs {
+                    let Some(ind) =
+                        errors.handle(index.base10_parse::<usize>().map_err(Into::into))
+                    else {
+                        continue;
+                    };
+
+                    if seen.contains(&ind) {
+                        errors.push(
+                            darling::Error::custom(format!("statement {ind} is taken twice"))
+                                .with_span(index),
+                        );
+                    }
+                    seen.push(ind);
+                }
+
+                errors.finish_with(Self::Indexed(outputs))
+            }
+            Meta::Path(_) => Err(darling::Error::unsupported_format("word")),
+        }
+    }
+}
+
+struct IndexedOutput {
+    index: LitInt,
+    ty: LitStr,
+}
+
+impl Parse for IndexedOutput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let index = input.parse()?;
+        input.parse::<Token![=]>()?;
+        let ty = input.parse()?;
+
+        Ok(Self { index, ty })
+    }
+}
+
 #[derive(FromField)]
+#[darling(attributes(var), and_then = DBQueryField::validate)]
 struct DBQueryField {
     vis: Visibility,
     ident: Option<Ident>,
     ty: Type,
+
+    /// bind name, `$rename` instead of `$field`
+    rename: Option<Ident>,
+    /// not bound, only used in rust
+    skip: Flag,
+    /// `fn(T) -> impl SurrealValue` applied before binding
+    with: Option<Path>,
+    /// bound as a table name, `{field}` becomes `type::table($field)`
+    table: Flag,
 }
 
 impl DBQueryField {
@@ -179,15 +649,131 @@ impl DBQueryField {
         }
     }
 
-    fn build_query_binds(list: &[Self]) -> impl Iterator<Item = TokenStream> {
-        list.iter().enumerate().map(|(ind, Self { ident, .. })| {
-            let ident_str = ident
-                .as_ref()
-                .map(|i| i.to_string())
-                .unwrap_or_else(|| format!("var{ind}"));
-            let ident_lit_str = LitStr::new(&ident_str, ident.span());
+    /// `Option<T>`, decides if an optional fragment is included
+    fn is_option(&self) -> bool {
+        match &self.ty {
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .is_some_and(|segment| segment.ident == "Option"),
+            _ => false,
+        }
+    }
+
+    /// `{placeholder}` name, the field name or its index for tuple structs
+    fn placeholder(&self, ind: usize) -> String {
+        self.ident
+            .as_ref()
+            .map(|i| i.to_string())
+            .unwrap_or_else(|| ind.to_string())
+    }
+
+    fn validate(self) -> darling::Result<Self> {
+        if self.skip.is_present()
+            && (self.rename.is_some() || self.with.is_some() || self.table.is_present())
+        {
+            return Err(darling::Error::custom(
+                "`skip` can't be combined with `rename`, `with` or `table`",
+            )
+            .with_span(&self.skip.span()));
+        }
 
-            quote!(.bind((#ident_lit_str, #ident)))
-        })
+        Ok(self)
+    }
+
+    /// `$var` name, `rename`, the field name or `var{index}` for tuple structs
+    fn var_name(&self, ind: usize) -> String {
+        match &self.rename {
+            Some(rename) => rename.to_string(),
+            None => self.var_ident(ind).to_string(),
+        }
+    }
+
+    /// local binding when destructuring self
+    fn var_ident(&self, ind: usize) -> Ident {
+        self.ident
+            .clone()
+            .unwrap_or_else(|| Ident::new(&format!("var{ind}"), self.span()))
+    }
+
+    fn build_query_binds<'a>(
+        list: &'a [Self],
+        vars: &'a Ident,
+    ) -> impl Iterator<Item = TokenStream> + 'a {
+        list.iter()
+            .enumerate()
+            .filter(|(_, field)| !field.skip.is_present())
+            .map(move |(ind, field)| {
+                let ident = field.var_ident(ind);
+                let var_lit_str = LitStr::new(&field.var_name(ind), ident.span());
+
+                let value = match &field.with {
+                    Some(with) => quote!(#with(#ident)),
+                    None => ident.to_token_stream(),
+                };
+                let value = match field.table.is_present() {
+                    true => quote!(surrealdb::types::Table::from(#value)),
+                    false => value,
+                };
+
+                quote!(#vars.insert(#var_lit_str, #value);)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(sql: &str) -> Vec<&str> {
+        placeholders(sql).map(|(_, name)| name).collect()
+    }
+
+    #[test]
+    fn placeholders_are_identifiers_or_indexes() {
+        let sql = "SELECT * FROM {table} WHERE a = {0} AND b = {not valid} AND c = {1x} AND d = {}";
+        assert_eq!(names(sql), ["table", "0"]);
+
+        let (range, _) = placeholders(sql).next().unwrap();
+        assert_eq!(&sql[range], "{table}");
+    }
+
+    #[test]
+    fn placeholders_skip_strings_and_comments() {
+        let sql = "SELECT * FROM person WHERE name = '{name}' AND a = \"{a}\" -- {b}
+            AND c = {c} /* {d} */ AND e = `{e}` // {f}
+            # {g}
+            AND h = 'it\\'s {h}' AND i = {i}";
+        assert_eq!(names(sql), ["c", "i"]);
+    }
+
+    #[test]
+    fn fragments_need_a_placeholder() {
+        let sql = "SELECT * FROM person WHERE id INSIDE [[1, 2]] [[ AND age >= {min_age} ]] \
+            [[ AND name = '[[{name}]]' ]] [[ AND city = {city} ]]";
+        let fragments = fragments(sql)
+            .into_iter()
+            .map(|range| &sql[range])
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            fragments,
+            ["[[ AND age >= {min_age} ]]", "[[ AND city = {city} ]]"]
+        );
+    }
+
+    #[test]
+    fn unclosed_fragment_is_ignored() {
+        assert!(fragments("SELECT * FROM person [[ WHERE age >= {min_age}").is_empty());
+    }
+
+    #[test]
+    fn references_var_as_whole_name() {
+        assert!(references_var("SELECT * FROM $table", "table"));
+        assert!(references_var("WHERE a = $a;", "a"));
+        assert!(!references_var("WHERE a = $ab", "a"));
+        assert!(!references_var("WHERE a = 'costs $a'", "a"));
+        assert!(!references_var("WHERE a = 1 -- $a", "a"));
     }
 }
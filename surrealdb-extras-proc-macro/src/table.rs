@@ -1,18 +1,19 @@
 use std::fmt::Display;
 
 use darling::{
-    FromDeriveInput, FromField,
+    FromDeriveInput, FromField, FromMeta,
     ast::Data,
     util::{Flag, Ignored},
 };
+use heck::ToShoutySnakeCase;
 use proc_macro2::{Span, TokenStream};
 use quote::{ToTokens, quote};
 use syn::{
     AngleBracketedGenericArguments, Expr, Ident, LitStr, PathSegment, Type, TypeParen, TypePath,
-    spanned::Spanned,
+    Visibility, spanned::Spanned,
 };
 
-use crate::util::DeriveInputUtil;
+use crate::util::{DeriveInputUtil, check_sql};
 
 #[derive(FromDeriveInput)]
 #[darling(supports(struct_named))]
@@ -30,17 +31,17 @@ impl DeriveInputUtil for SurrealSelect {
             Data::Struct(fields) => fields,
         };
 
-        let keys = [Ident::new("id", Span::mixed_site())].into_iter().chain(
+        let keys = ["id".to_string()].into_iter().chain(
             fields
                 .iter()
                 .filter(|&f| !f.exclude.is_present())
-                .map(|f| f.field_name().clone()),
+                .map(SurrealSelectTableField::db_name),
         );
 
         Ok(quote! {
                 impl surrealdb_extras::SurrealSelectInfo for #ident {
                 fn keys()-> &'static [&'static str] {
-                    &[#( stringify!(#keys) ),*]
+                    &[#( #keys ),*]
                 }
             }
         })
@@ -55,6 +56,14 @@ pub struct SurrealTable {
 
     sql: Option<Vec<LitStr>>,
     db: Ident,
+    relation: Option<RelationAttr>,
+    view: Option<LitStr>,
+    permissions: Option<PermissionsAttr>,
+    // `default` keeps darling from expanding to `index: index`
+    #[darling(multiple, default)]
+    index: Vec<IndexAttr>,
+    #[darling(multiple, default)]
+    event: Vec<EventAttr>,
 }
 
 impl DeriveInputUtil for SurrealTable {
@@ -65,6 +74,11 @@ impl DeriveInputUtil for SurrealTable {
 
             sql,
             db,
+            relation,
+            view,
+            permissions,
+            index: indexes,
+            event: events,
         } = self;
 
         let keys = SurrealSelect {
@@ -83,44 +97,107 @@ impl DeriveInputUtil for SurrealTable {
             .filter(|&f| f.exclude.is_present())
             .map(SurrealSelectTableField::field_name);
 
-        let mut err_emitter = manyhow::Emitter::new();
+        let sql = sql.iter().flatten().collect::<Vec<_>>();
+        for sql in &sql {
+            check_sql(&sql.value(), sql.span())?;
+        }
 
-        let sql = sql
-            .iter()
-            .flatten()
-            .map(|x| quote!(surrealdb_extras::sql!(#x).into()));
+        // checked like the statements `TableDef::statements` renders
+        let mut table_sql = format!("DEFINE TABLE {db}");
+        if let Some(relation) = relation {
+            table_sql.push_str(&format!(" {}", relation.render()));
+        }
+        if let Some(view) = view {
+            table_sql.push_str(&format!(" AS {}", view.value()));
+        }
+        if let Some(permissions) = permissions {
+            table_sql.push_str(&format!(" {}", permissions.render(true)));
+        }
+        check_sql(&table_sql, db.span())?;
 
-        let define_field_queries = fields
+        let field_defs = fields
             .iter()
             .map(|f| {
                 let name = f.field_name();
-                let ty = f
-                    .db_type
-                    .as_ref()
-                    .map(|t| manyhow::Result::Ok(t.to_token_stream()))
-                    .unwrap_or_else(|| -> manyhow::Result<_> {
-                        let ty = f.surreal_ty()?;
-                        Ok(ty.to_token_stream())
-                    })?;
-
-                let lit_sql_str = LitStr::new(
-                    &format!("DEFINE FIELD {name} ON TABLE {db} TYPE {ty}"),
-                    name.span(),
-                );
-
-                Ok(quote!(surrealdb_extras::sql!(#lit_sql_str).into()))
+                let kind = match &f.db_type {
+                    Some(ty) => ty.to_string(),
+                    None => f.surreal_ty()?.to_string().replace(' ', ""),
+                };
+                if let Some(record) = &f.record
+                    && !kind.contains("record<")
+                {
+                    manyhow::bail!(
+                        record.span(),
+                        "`record = {record}` needs a `RecordId` field, the type is `{kind}`"
+                    );
+                }
+                let field_def = FieldDefTokens {
+                    name: f.db_name(),
+                    kind,
+                    permissions: f.permissions.as_ref(),
+                };
+                check_sql(&field_def.render(&db.to_string()), name.span())?;
+
+                Ok(field_def)
             })
             .collect::<manyhow::Result<Vec<_>>>()?;
 
-        err_emitter.into_result()?;
+        for index in indexes {
+            check_sql(&index.render(&db.to_string()), index.name.span())?;
+        }
+        for event in events {
+            check_sql(&event.render(&db.to_string()), event.name.span())?;
+        }
 
-        let attr = [{
-            let str = LitStr::new(&format!("DEFINE TABLE {db}"), db.span());
-            quote!(surrealdb_extras::sql!(#str).into())
-        }]
-        .into_iter()
-        .chain(define_field_queries)
-        .chain(sql);
+        let table_name = db.to_string();
+        let table_permissions = option_tokens(permissions.as_ref().map(PermissionsAttr::to_tokens));
+        let relation = option_tokens(relation.as_ref().map(RelationAttr::to_tokens));
+        let view = option_tokens(view.as_ref().map(|view| quote!(#view.to_string())));
+        let sql = sql.iter().map(|sql| quote!(#sql.to_string()));
+
+        let field_consts = fields.iter().map(|f| {
+            let SurrealSelectTableField {
+                ident: field,
+                vis,
+                ty,
+                ..
+            } = f;
+            let field = field.as_ref().unwrap();
+            let name = LitStr::new(&f.db_name(), field.span());
+            let const_ident = Ident::new(
+                &field
+                    .to_string()
+                    .trim_start_matches("r#")
+                    .to_shouty_snake_case(),
+                field.span(),
+            );
+
+            // looked up by db name in `filter!`
+            let hidden_ident = filter_field_ident(f.field_name());
+
+            quote! {
+                #vis const #const_ident: surrealdb_extras::Field<#ident, #ty> =
+                    surrealdb_extras::Field::new(#name);
+                #[doc(hidden)]
+                #[allow(non_upper_case_globals)]
+                #vis const #hidden_ident: surrealdb_extras::Field<#ident, #ty> = Self::#const_ident;
+            }
+        });
+        let id_const = fields
+            .iter()
+            .all(|f| f.ident.as_ref().unwrap() != "id")
+            .then(|| {
+                let hidden_ident = filter_field_ident(&Ident::new("id", Span::call_site()));
+
+                quote! {
+                    pub const ID: surrealdb_extras::Field<#ident, surrealdb_extras::RecordIdType<#ident>> =
+                        surrealdb_extras::Field::new("id");
+                    #[doc(hidden)]
+                    #[allow(non_upper_case_globals)]
+                    pub const #hidden_ident: surrealdb_extras::Field<#ident, surrealdb_extras::RecordIdType<#ident>> =
+                        Self::ID;
+                }
+            });
 
         Ok(quote! {
             #keys
@@ -138,12 +215,26 @@ impl DeriveInputUtil for SurrealTable {
                     &[#( #exc ),*]
                 }
 
-                fn funcs() ->  Vec<String>{
-                    vec![#( #attr ),*]
+                fn table_def() -> surrealdb_extras::schema::TableDef {
+                    surrealdb_extras::schema::TableDef {
+                        name: #table_name.to_string(),
+                        relation: #relation,
+                        view: #view,
+                        permissions: #table_permissions,
+                        fields: vec![#( #field_defs ),*],
+                        indexes: vec![#( #indexes ),*],
+                        events: vec![#( #events ),*],
+                        statements: vec![#( #sql ),*],
+                    }
                 }
             }
 
+            surrealdb_extras::__submit_table!(#ident);
+
             impl #ident {
+                #id_const
+                #(#field_consts)*
+
                 pub fn add<'a: 'b, 'b, D: surrealdb::Connection>(
                     self,
                     conn: &'a surrealdb::Surreal<D>
@@ -163,16 +254,275 @@ impl DeriveInputUtil for SurrealTable {
     }
 }
 
+/// `permissions(select = "FULL", create = "WHERE ..")`, missing ones are `NONE`
+#[derive(Clone, FromMeta)]
+struct PermissionsAttr {
+    select: Option<LitStr>,
+    create: Option<LitStr>,
+    update: Option<LitStr>,
+    delete: Option<LitStr>,
+}
+
+impl PermissionsAttr {
+    fn each(&self) -> [(&'static str, Option<&LitStr>); 4] {
+        [
+            ("select", self.select.as_ref()),
+            ("create", self.create.as_ref()),
+            ("update", self.update.as_ref()),
+            ("delete", self.delete.as_ref()),
+        ]
+    }
+
+    /// like `Permissions` renders, fields have no `delete`
+    fn render(&self, delete: bool) -> String {
+        let permissions = self
+            .each()
+            .into_iter()
+            .filter(|(op, _)| delete || *op != "delete")
+            .map(|(op, permission)| {
+                let permission = permission.map(LitStr::value).unwrap_or("NONE".to_string());
+                let permission = permission.trim();
+                match ["FULL", "NONE"]
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(permission))
+                    || permission.to_ascii_uppercase().starts_with("WHERE ")
+                {
+                    true => format!("FOR {op} {permission}"),
+                    false => format!("FOR {op} WHERE {permission}"),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        format!("PERMISSIONS {}", permissions.join(", "))
+    }
+
+    fn to_tokens(&self) -> TokenStream {
+        let [select, create, update, delete] = self.each().map(|(_, permission)| {
+            let permission = permission
+                .cloned()
+                .unwrap_or_else(|| LitStr::new("NONE", Span::call_site()));
+            quote!(surrealdb_extras::schema::Permission::parse(#permission))
+        });
+
+        quote! {
+            surrealdb_extras::schema::Permissions {
+                select: #select,
+                create: #create,
+                update: #update,
+                delete: #delete,
+            }
+        }
+    }
+}
+
+/// `relation(from = "person", to = "post | comment", enforced)`
+#[derive(Clone, FromMeta)]
+struct RelationAttr {
+    from: Option<LitStr>,
+    to: Option<LitStr>,
+    enforced: Flag,
+}
+
+impl RelationAttr {
+    fn tables(tables: Option<&LitStr>) -> Vec<String> {
+        tables
+            .map(LitStr::value)
+            .iter()
+            .flat_map(|tables| tables.split('|'))
+            .map(|table| table.trim().to_string())
+            .collect()
+    }
+
+    /// like `RelationDef` renders
+    fn render(&self) -> String {
+        let mut res = "TYPE RELATION".to_string();
+        for (keyword, tables) in [("IN", self.from.as_ref()), ("OUT", self.to.as_ref())] {
+            let tables = Self::tables(tables);
+            if !tables.is_empty() {
+                res.push_str(&format!(" {keyword} {}", tables.join(" | ")));
+            }
+        }
+        if self.enforced.is_present() {
+            res.push_str(" ENFORCED");
+        }
+
+        res
+    }
+
+    fn to_tokens(&self) -> TokenStream {
+        let from = Self::tables(self.from.as_ref());
+        let to = Self::tables(self.to.as_ref());
+        let enforced = self.enforced.is_present();
+
+        quote! {
+            surrealdb_extras::schema::RelationDef {
+                from: vec![#( #from.to_string() ),*],
+                to: vec![#( #to.to_string() ),*],
+                enforced: #enforced,
+            }
+        }
+    }
+}
+
+/// `index(name = idx, fields = "a, b", unique)`
+#[derive(Clone, FromMeta)]
+struct IndexAttr {
+    name: Ident,
+    fields: LitStr,
+    unique: Flag,
+}
+
+impl IndexAttr {
+    fn fields(&self) -> Vec<String> {
+        self.fields
+            .value()
+            .split(',')
+            .map(|field| field.trim().to_string())
+            .collect()
+    }
+
+    fn render(&self, table: &str) -> String {
+        format!(
+            "DEFINE INDEX {} ON TABLE {table} FIELDS {}{}",
+            self.name,
+            self.fields().join(", "),
+            if self.unique.is_present() {
+                " UNIQUE"
+            } else {
+                ""
+            }
+        )
+    }
+}
+
+impl ToTokens for IndexAttr {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = self.name.to_string();
+        let fields = self.fields();
+        let unique = self.unique.is_present();
+
+        tokens.extend(quote! {
+            surrealdb_extras::schema::IndexDef {
+                name: #name.to_string(),
+                fields: vec![#( #fields.to_string() ),*],
+                unique: #unique,
+            }
+        });
+    }
+}
+
+/// `event(name = ev, when = "$event = 'CREATE'", then = "CREATE log")`
+#[derive(Clone, FromMeta)]
+struct EventAttr {
+    name: Ident,
+    when: Option<LitStr>,
+    then: LitStr,
+}
+
+impl EventAttr {
+    fn render(&self, table: &str) -> String {
+        let Self { name, when, then } = self;
+
+        match when {
+            Some(when) => format!(
+                "DEFINE EVENT {name} ON TABLE {table} WHEN {} THEN {}",
+                when.value(),
+                then.value()
+            ),
+            None => format!("DEFINE EVENT {name} ON TABLE {table} THEN {}", then.value()),
+        }
+    }
+}
+
+impl ToTokens for EventAttr {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = self.name.to_string();
+        let when = option_tokens(self.when.as_ref().map(|when| quote!(#when.to_string())));
+        let then = &self.then;
+
+        tokens.extend(quote! {
+            surrealdb_extras::schema::EventDef {
+                name: #name.to_string(),
+                when: #when,
+                then: #then.to_string(),
+            }
+        });
+    }
+}
+
+/// `FieldDef` of a struct field
+struct FieldDefTokens<'a> {
+    name: String,
+    kind: String,
+    permissions: Option<&'a PermissionsAttr>,
+}
+
+impl FieldDefTokens<'_> {
+    fn render(&self, table: &str) -> String {
+        let Self {
+            name,
+            kind,
+            permissions,
+        } = self;
+
+        let mut res = format!("DEFINE FIELD {name} ON TABLE {table} TYPE {kind}");
+        if let Some(permissions) = permissions {
+            res.push_str(&format!(" {}", permissions.render(false)));
+        }
+
+        res
+    }
+}
+
+impl ToTokens for FieldDefTokens<'_> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let Self {
+            name,
+            kind,
+            permissions,
+        } = self;
+
+        let permissions = option_tokens(permissions.map(PermissionsAttr::to_tokens));
+
+        tokens.extend(quote! {
+            surrealdb_extras::schema::FieldDef {
+                name: #name.to_string(),
+                kind: #kind.to_string(),
+                permissions: #permissions,
+            }
+        });
+    }
+}
+
+fn option_tokens(value: Option<TokenStream>) -> TokenStream {
+    match value {
+        Some(value) => quote!(Some(#value)),
+        None => quote!(None),
+    }
+}
+
+/// hidden `Field` constant named after the db field
+pub fn filter_field_ident(name: &Ident) -> Ident {
+    Ident::new(
+        &format!("__field_{}", name.to_string().trim_start_matches("r#")),
+        name.span(),
+    )
+}
+
 #[derive(Clone, FromField)]
 #[darling(attributes(opt))]
 struct SurrealSelectTableField {
     ident: Option<Ident>,
+    vis: Visibility,
     ty: Type,
 
     rename: Option<Ident>,
     // TODO: Support more complex types
     db_type: Option<Ident>,
+    /// table of a `RecordId` or `RecordIdType`, `record<table>` instead of `record`
+    record: Option<Ident>,
     exclude: Flag,
+    permissions: Option<PermissionsAttr>,
 }
 
 impl SurrealSelectTableField {
@@ -182,16 +532,30 @@ impl SurrealSelectTableField {
             .unwrap_or_else(|| self.ident.as_ref().unwrap())
     }
 
+    /// [field_name](Self::field_name) without `r#`, e.g. `type` of `r#type`
+    fn db_name(&self) -> String {
+        self.field_name()
+            .to_string()
+            .trim_start_matches("r#")
+            .to_string()
+    }
+
     fn surreal_ty(&self) -> manyhow::Result<SurrealTy> {
         match &self.db_type {
             Some(db_ty) => Ok(db_ty.clone().into()),
-            None => Self::from_ty_to_surreal_ty(&self.ty, true),
+            None => Self::from_ty_to_surreal_ty(&self.ty, true, self.record.as_ref()),
         }
     }
 
-    fn from_ty_to_surreal_ty(ty: &Type, primary: bool) -> manyhow::Result<SurrealTy> {
+    fn from_ty_to_surreal_ty(
+        ty: &Type,
+        primary: bool,
+        record: Option<&Ident>,
+    ) -> manyhow::Result<SurrealTy> {
         match ty {
-            Type::Paren(TypeParen { elem, .. }) => Self::from_ty_to_surreal_ty(elem, primary),
+            Type::Paren(TypeParen { elem, .. }) => {
+                Self::from_ty_to_surreal_ty(elem, primary, record)
+            }
             Type::Path(TypePath { path, .. }) => {
                 let PathSegment { ident, arguments } = path
                     .segments
@@ -208,18 +572,20 @@ impl SurrealSelectTableField {
                             .map(|arg| match arg {
                                 syn::GenericArgument::Type(ty) => {
                                     Ok(SurrealTableFieldTypeArg::Type(Self::from_ty_to_surreal_ty(
-                                        ty, false,
+                                        ty, false, record,
                                     )?))
                                 }
                                 syn::GenericArgument::Const(expr) => {
                                     Ok(SurrealTableFieldTypeArg::Const(expr))
                                 }
-                                _ => manyhow::bail!(arg.span(), "Argument type not supported!"),
+                                _ => {
+                                    manyhow::bail!(arg.span(), "Argument type not supported!");
+                                }
                             })
                             .collect::<manyhow::Result<Vec<_>>>()?,
                     ),
                     syn::PathArguments::Parenthesized(_) => {
-                        manyhow::bail!(arguments.span(), "Unsupported arguments!")
+                        manyhow::bail!(arguments.span(), "Unsupported arguments!");
                     }
                 };
 
@@ -239,9 +605,13 @@ impl SurrealSelectTableField {
                         None => Ident::new("array", ident.span()),
                     },
                     "bool" => return Ok(Ident::new("bool", ident.span()).into()),
-                    "DateTime" => return Ok(Ident::new("datetime", ident.span()).into()),
+                    "DateTime" | "Datetime" => {
+                        return Ok(Ident::new("datetime", ident.span()).into());
+                    }
                     "Duration" => return Ok(Ident::new("duration", ident.span()).into()),
-                    "f128" => return Ok(Ident::new("decimal", ident.span()).into()),
+                    "Decimal" | "f128" => return Ok(Ident::new("decimal", ident.span()).into()),
+                    "Number" => return Ok(Ident::new("number", ident.span()).into()),
+                    "Uuid" => return Ok(Ident::new("uuid", ident.span()).into()),
                     "f16" | "f32" | "f64" => return Ok(Ident::new("float", ident.span()).into()),
                     "i8" | "i16" | "i32" | "i64" | "u16" | "u32" => {
                         return Ok(Ident::new("int", ident.span()).into());
@@ -258,8 +628,13 @@ impl SurrealSelectTableField {
                         .into());
                     }
                     "Option" => Ident::new("option", ident.span()),
-                    // TODO: add type which record
-                    "RecordId" => Ident::new("record", ident.span()),
+                    "RecordId" | "RecordIdType" | "RecordIdFunc" => match record {
+                        Some(record) => {
+                            return Ok(SurrealTy::Combined(quote!(record<#record>)));
+                        }
+                        None if ident == "RecordId" => Ident::new("record", ident.span()),
+                        None => return Ok(Ident::new("object", ident.span()).into()),
+                    },
                     "HashSet" => Ident::new("set", ident.span()),
                     "String" => return Ok(Ident::new("string", ident.span()).into()),
                     // TODO: geometry
@@ -274,7 +649,9 @@ impl SurrealSelectTableField {
 
                 Ok(SurrealTy::Combined(quote!(#primary_ty #gens)))
             }
-            _ => manyhow::bail!(ty.span(), "Unsupported type!"),
+            _ => {
+                manyhow::bail!(ty.span(), "Unsupported type!");
+            }
         }
     }
 }
@@ -1,6 +1,9 @@
+use std::path::PathBuf;
+
 use darling::FromDeriveInput;
-use proc_macro2::TokenStream;
-use syn::DeriveInput;
+use proc_macro2::{Span, TokenStream};
+use surrealdb_core::dbs::{Capabilities, capabilities::Targets};
+use syn::{DeriveInput, LitStr};
 
 pub trait DeriveInputUtil: FromDeriveInput {
     fn parse(input: TokenStream) -> manyhow::Result<Self> {
@@ -12,3 +15,40 @@ pub trait DeriveInputUtil: FromDeriveInput {
 
     fn gen_(&self) -> manyhow::Result<TokenStream>;
 }
+
+/// parses SurrealQL like the server would, with every capability enabled,
+/// and returns the number of statements
+pub fn check_sql(sql: &str, span: Span) -> manyhow::Result<usize> {
+    match parse_sql(sql) {
+        Ok(statements) => Ok(statements),
+        Err(err) => {
+            manyhow::bail!(span, "{err}");
+        }
+    }
+}
+
+/// [check_sql] without a span
+pub fn parse_sql(sql: &str) -> Result<usize, String> {
+    let mut capabilities = Capabilities::all();
+    *capabilities.allowed_experimental_features_mut() = Targets::All;
+
+    surrealdb_core::syn::parse_with_capabilities(sql, &capabilities)
+        .map(|ast| ast.num_statements())
+        .map_err(|err| err.to_string())
+}
+
+/// reads a file relative to `CARGO_MANIFEST_DIR`, returns its absolute path and content
+pub fn read_manifest_file(path: &LitStr) -> manyhow::Result<(LitStr, String)> {
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR")
+        .map_err(|err| manyhow::error_message!(path.span(), "CARGO_MANIFEST_DIR: {err}"))?;
+    let full_path = PathBuf::from(manifest_dir).join(path.value());
+
+    let content = std::fs::read_to_string(&full_path).map_err(|err| {
+        manyhow::error_message!(path.span(), "failed to read {}: {err}", full_path.display())
+    })?;
+
+    Ok((
+        LitStr::new(&full_path.to_string_lossy(), path.span()),
+        content,
+    ))
+}
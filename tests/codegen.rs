@@ -0,0 +1,58 @@
+//! the structs `codegen::from_surql` generates for `codegen/schema.surql` compile
+//! and define the same schema as the script
+
+use surrealdb::{Surreal, engine::local::Mem};
+use surrealdb_extras::{
+    DefineStrategy, SurrealExt, SurrealTableInfo, codegen, schema::export_schema,
+};
+
+mod tables {
+    include!("codegen/tables.rs");
+}
+
+const SCHEMA: &str = include_str!("codegen/schema.surql");
+
+#[test]
+fn generated_code_is_current() {
+    assert_eq!(
+        codegen::from_surql(SCHEMA).unwrap(),
+        include_str!("codegen/tables.rs")
+    );
+}
+
+async fn conn(sql: &str) -> Surreal<surrealdb::engine::local::Db> {
+    let conn = Surreal::new::<Mem>(()).await.unwrap();
+    conn.query("DEFINE NAMESPACE test; USE NS test; DEFINE DATABASE test;")
+        .await
+        .unwrap()
+        .check()
+        .unwrap();
+    conn.use_ns("test").use_db("test").await.unwrap();
+    conn.query(sql).await.unwrap().check().unwrap();
+    conn
+}
+
+#[tokio::test]
+async fn round_trip() {
+    let registers = [
+        tables::Company::register().unwrap(),
+        tables::Log::register().unwrap(),
+        tables::Person::register().unwrap(),
+        tables::PersonCount::register().unwrap(),
+        tables::WorksAt::register().unwrap(),
+    ];
+    let exported = export_schema("test", "test", registers, DefineStrategy::Missing).unwrap();
+
+    let expected = conn(SCHEMA).await;
+    let generated = conn(&exported).await;
+
+    let tables = expected.info_db().await.unwrap().tables;
+    assert_eq!(tables, generated.info_db().await.unwrap().tables);
+    for table in tables.keys() {
+        assert_eq!(
+            expected.info_table(table).await.unwrap(),
+            generated.info_table(table).await.unwrap(),
+            "{table}"
+        );
+    }
+}
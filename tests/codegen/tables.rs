@@ -0,0 +1,69 @@
+// generated by surrealdb-extras
+
+use surrealdb::types::SurrealValue;
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SurrealValue, surrealdb_extras::SurrealTable)]
+#[table(
+    db = company,
+    permissions(select = "FULL"),
+    sql(
+        "DEFINE TABLE OVERWRITE company TYPE NORMAL SCHEMAFULL PERMISSIONS FOR select FULL, FOR create, update, delete NONE",
+    ),
+)]
+pub struct Company {
+    pub founded: Option<surrealdb::types::Datetime>,
+    pub name: String,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SurrealValue, surrealdb_extras::SurrealTable)]
+#[table(
+    db = log,
+)]
+pub struct Log {}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SurrealValue, surrealdb_extras::SurrealTable)]
+#[table(
+    db = person,
+    index(name = person_name, fields = "name", unique),
+    event(name = person_created, when = "$event = 'CREATE'", then = "CREATE log SET person = $after.id"),
+    sql(
+        "DEFINE TABLE OVERWRITE person TYPE NORMAL SCHEMAFULL PERMISSIONS NONE",
+        "DEFINE FIELD OVERWRITE created ON person TYPE datetime DEFAULT time::now() READONLY PERMISSIONS FULL",
+        "DEFINE FIELD OVERWRITE name ON person TYPE string ASSERT string::len($value) > 0 PERMISSIONS FULL",
+    ),
+)]
+pub struct Person {
+    pub age: i64,
+    pub balance: surrealdb::types::Decimal,
+    pub created: surrealdb::types::Datetime,
+    #[opt(record = company)]
+    pub employer: Option<surrealdb_extras::RecordIdType<Company>>,
+    pub name: String,
+    pub score: surrealdb::types::Number,
+    pub session: surrealdb::types::Duration,
+    pub tags: Vec<String>,
+    pub token: surrealdb::types::Uuid,
+    #[surreal(rename = "type")]
+    pub r#type: String,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SurrealValue, surrealdb_extras::SurrealTable)]
+#[table(
+    db = person_count,
+    view = "SELECT count() AS total FROM person GROUP ALL",
+)]
+pub struct PersonCount {}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize, SurrealValue, surrealdb_extras::SurrealTable)]
+#[table(
+    db = works_at,
+    relation(from = "person", to = "company", enforced),
+)]
+pub struct WorksAt {
+    #[opt(record = person)]
+    #[surreal(rename = "in")]
+    pub r#in: surrealdb_extras::RecordIdType<Person>,
+    #[opt(record = company)]
+    pub out: surrealdb_extras::RecordIdType<Company>,
+    pub since: surrealdb::types::Datetime,
+}